@@ -0,0 +1,73 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    fs,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use crossbeam::channel::Receiver;
+use rustyorffinder::{
+    error::error::OrfFinderError,
+    finder::{
+        finder::run_pipeline, finder_config::FinderConfig, orf_search::OrfSearch,
+        threaded_finder::ThreadedFinder,
+    },
+    models::models::ORF,
+    outwriter::outwriter::OutWriter,
+};
+
+/// Counts the allocated bytes, so that the memory traffic of a run can be reported next to its throughput
+struct CountingAllocator;
+
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        return System.alloc(layout);
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Drains all ORFs without writing them, so that only the finder is measured
+struct DrainWriter {}
+
+impl OutWriter for DrainWriter {
+    fn write(&self, orfs: Receiver<ORF>) -> Result<(), OrfFinderError> {
+        for _ in orfs {}
+        return Ok(());
+    }
+}
+
+fn run_finder(sequence: &str) {
+    let search = OrfSearch::new(sequence.to_string(), &FinderConfig::default()).unwrap();
+    let finder = ThreadedFinder::new(search, 3);
+    run_pipeline(&finder, &[], &DrainWriter {}).unwrap();
+}
+
+fn threaded_finder_benchmark(c: &mut Criterion) {
+    let sequence = fs::read_to_string("resources/sequences/NC_011604.1_normal.fasta").unwrap();
+
+    let allocated_before = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    run_finder(&sequence);
+    let allocated = ALLOCATED_BYTES.load(Ordering::Relaxed) - allocated_before;
+    println!(
+        "NC_011604.1: {} bases, {} bytes allocated per run",
+        sequence.len(),
+        allocated
+    );
+
+    let mut group = c.benchmark_group("threaded_finder");
+    group.throughput(Throughput::Bytes(sequence.len() as u64));
+    group.bench_function("NC_011604.1", |b| b.iter(|| run_finder(&sequence)));
+    group.finish();
+}
+
+criterion_group!(benches, threaded_finder_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,135 @@
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader},
+    path::PathBuf,
+};
+
+use crate::{error::error::OrfFinderError, models::models::Direction};
+
+use super::decompress::decompress;
+
+/// The annotated coding sequence of a transcript.
+/// Positions are located on the forward strand of the transcript, start at 0 and the end is exclusive.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AnnotatedCds {
+    pub start: usize,
+    pub end: usize,
+    pub direction: Direction,
+}
+
+/// Reads the CDS features of a GFF3 or GTF file, which may be compressed.
+/// The features have to be located on the transcripts, so each CDS belongs to the sequence named in the first column.
+/// Several CDS features of a transcript are combined into one spanning all of them.
+pub fn parse_cds_annotation_file(
+    path: PathBuf,
+) -> Result<HashMap<String, AnnotatedCds>, OrfFinderError> {
+    let annotation_error = |message: String| {
+        OrfFinderError::AnnotationParse(format!("{}: {}", path.display(), message))
+    };
+
+    let file = std::fs::File::open(path.as_path())
+        .and_then(|file| decompress(Box::new(file)))
+        .map_err(|err| annotation_error(err.to_string()))?;
+    let mut annotation: HashMap<String, AnnotatedCds> = HashMap::new();
+
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|err| annotation_error(err.to_string()))?;
+        // GFF3 files may end with the sequences
+        if line.starts_with("##FASTA") {
+            break;
+        }
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() < 8 {
+            return Err(annotation_error(format!(
+                "line {} has less than 8 columns",
+                index + 1
+            )));
+        }
+        if fields[2] != "CDS" {
+            continue;
+        }
+
+        let position = |column: usize| -> Result<usize, OrfFinderError> {
+            return fields[column]
+                .parse()
+                .map_err(|_| annotation_error(format!("line {} is malformed", index + 1)));
+        };
+        let (start, end) = (position(3)?, position(4)?);
+        let direction = match fields[6] {
+            "+" => Direction::FORWARD,
+            "-" => Direction::REVERSE,
+            _ => {
+                return Err(annotation_error(format!(
+                    "the CDS in line {} has no strand",
+                    index + 1
+                )))
+            }
+        };
+        if start == 0 || end < start {
+            return Err(annotation_error(format!(
+                "the CDS in line {} has invalid positions",
+                index + 1
+            )));
+        }
+
+        let cds = AnnotatedCds {
+            start: start - 1,
+            end: end,
+            direction: direction,
+        };
+        match annotation.get_mut(fields[0]) {
+            Some(known) if known.direction != direction => {
+                return Err(annotation_error(format!(
+                    "the CDS of {} is annotated on both strands",
+                    fields[0]
+                )));
+            }
+            Some(known) => {
+                known.start = known.start.min(cds.start);
+                known.end = known.end.max(cds.end);
+            }
+            None => {
+                annotation.insert(fields[0].to_string(), cds);
+            }
+        }
+    }
+
+    return Ok(annotation);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{models::models::Direction, test_utils::TempFile};
+
+    use super::{parse_cds_annotation_file, AnnotatedCds};
+
+    #[test]
+    fn combines_cds_features_of_gtf_and_gff3() {
+        let file = TempFile::new(
+            "annotation.gtf",
+            "##gff-version 3\n\
+             tx1\tsource\texon\t1\t500\t.\t+\t.\tgene_id \"g1\"; transcript_id \"tx1\";\n\
+             tx1\tsource\tCDS\t101\t200\t.\t+\t0\tgene_id \"g1\"; transcript_id \"tx1\";\n\
+             tx1\tsource\tCDS\t201\t303\t.\t+\t2\tgene_id \"g1\"; transcript_id \"tx1\";\n\
+             tx2\tsource\tCDS\t11\t40\t.\t-\t0\tID=cds.tx2;Parent=tx2\n\
+             ##FASTA\n>tx1\nACGT\n",
+        );
+
+        let annotation = parse_cds_annotation_file(file.path().to_path_buf()).unwrap();
+        assert_eq!(annotation.len(), 2);
+        assert_eq!(
+            annotation["tx1"],
+            AnnotatedCds {
+                start: 100,
+                end: 303,
+                direction: Direction::FORWARD
+            }
+        );
+        assert_eq!(annotation["tx2"].start, 10);
+        assert_eq!(annotation["tx2"].direction, Direction::REVERSE);
+    }
+}
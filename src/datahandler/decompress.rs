@@ -0,0 +1,115 @@
+use std::io::{self, Cursor, Read};
+
+use flate2::read::MultiGzDecoder;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+const BZIP2_MAGIC: &[u8] = b"BZh";
+/// Number of bytes required to detect all supported formats
+const MAGIC_LEN: usize = 4;
+
+/// Compression formats of the input, detected by their magic bytes.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Compression {
+    None,
+    /// Gzip, including the block compressed bgzip, which consists of concatenated gzip members
+    Gzip,
+    Zstd,
+    Bzip2,
+}
+
+impl Compression {
+    /// Detects the compression format from the first bytes of the input.
+    pub fn detect(magic: &[u8]) -> Self {
+        if magic.starts_with(GZIP_MAGIC) {
+            return Compression::Gzip;
+        }
+        if magic.starts_with(ZSTD_MAGIC) {
+            return Compression::Zstd;
+        }
+        if magic.starts_with(BZIP2_MAGIC) {
+            return Compression::Bzip2;
+        }
+
+        return Compression::None;
+    }
+}
+
+/// Wraps the input into a decoder of its compression format, uncompressed input is passed through.
+/// Only the magic bytes are read in advance, so this works on pipes like stdin as well.
+pub fn decompress(mut input: Box<dyn Read + Send>) -> io::Result<Box<dyn Read + Send>> {
+    let mut magic = Vec::with_capacity(MAGIC_LEN);
+    input
+        .by_ref()
+        .take(MAGIC_LEN as u64)
+        .read_to_end(&mut magic)?;
+    let compression = Compression::detect(&magic);
+
+    // The magic bytes are consumed already and have to be read again by the decoder
+    let input = Cursor::new(magic).chain(input);
+    let decompressed: Box<dyn Read + Send> = match compression {
+        Compression::None => Box::new(input),
+        Compression::Gzip => Box::new(MultiGzDecoder::new(input)),
+        Compression::Zstd => Box::new(zstd::stream::read::Decoder::new(input)?),
+        Compression::Bzip2 => Box::new(bzip2::read::MultiBzDecoder::new(input)),
+    };
+
+    return Ok(decompressed);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+
+    use super::{decompress, Compression};
+
+    const SEQUENCE: &[u8] = b">seq\nATGAAATAG\n";
+
+    fn decompressed(data: Vec<u8>) -> Vec<u8> {
+        let mut output = Vec::new();
+        decompress(Box::new(std::io::Cursor::new(data)))
+            .unwrap()
+            .read_to_end(&mut output)
+            .unwrap();
+
+        return output;
+    }
+
+    #[test]
+    fn detects_magic_bytes() {
+        assert_eq!(
+            Compression::detect(&[0x1f, 0x8b, 0x08, 0x04]),
+            Compression::Gzip
+        );
+        assert_eq!(
+            Compression::detect(&[0x28, 0xb5, 0x2f, 0xfd]),
+            Compression::Zstd
+        );
+        assert_eq!(Compression::detect(b"BZh9"), Compression::Bzip2);
+        assert_eq!(Compression::detect(b">seq"), Compression::None);
+        assert_eq!(Compression::detect(b"A"), Compression::None);
+    }
+
+    #[test]
+    fn decompresses_all_formats() {
+        // Two gzip members, as written by bgzip
+        let mut gzip = Vec::new();
+        for part in SEQUENCE.chunks(7) {
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+            encoder.write_all(part).unwrap();
+            gzip.extend(encoder.finish().unwrap());
+        }
+        assert_eq!(decompressed(gzip), SEQUENCE);
+
+        let zstd = zstd::stream::encode_all(SEQUENCE, 3).unwrap();
+        assert_eq!(decompressed(zstd), SEQUENCE);
+
+        let mut encoder = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::fast());
+        encoder.write_all(SEQUENCE).unwrap();
+        assert_eq!(decompressed(encoder.finish().unwrap()), SEQUENCE);
+
+        assert_eq!(decompressed(SEQUENCE.to_vec()), SEQUENCE);
+        assert_eq!(decompressed(b"A".to_vec()), b"A");
+    }
+}
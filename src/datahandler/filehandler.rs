@@ -1,43 +1,411 @@
-use bio::io::fasta;
+use std::{
+    borrow::Cow,
+    io::{BufReader, Cursor, Read},
+};
+
+use bio::io::{fasta, fastq};
 use clap::ValueEnum;
 
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+use crate::{
+    error::error::OrfFinderError,
+    finder::orf_search::DEFAULT_SEQID,
+    models::{models::Direction, packed_sequence::PackedSequence},
+};
+
+use super::{
+    decompress::decompress,
+    flatfile::{FlatFileFormat, FlatFileRecords},
+    twobit::{is_twobit, read_twobit},
+};
+
+/// Number of bytes inspected to detect the file type
+const SNIFF_LEN: usize = 64;
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
 pub enum SequenceFileType {
+    /// Detect the file type from the first bytes of the (decompressed) input
+    Auto,
     RawSequence,
     Fasta,
+    Fastq,
+    Genbank,
+    Embl,
+    /// UCSC 2bit
+    #[clap(name = "2bit")]
+    TwoBit,
+}
+
+impl SequenceFileType {
+    /// Detects the file type from the first bytes of the input, anything unknown is a raw sequence.
+    pub fn detect(prefix: &[u8]) -> Self {
+        if is_twobit(prefix) {
+            return SequenceFileType::TwoBit;
+        }
+
+        let start = prefix
+            .iter()
+            .position(|byte| !byte.is_ascii_whitespace())
+            .unwrap_or(prefix.len());
+        let prefix = &prefix[start..];
+        if prefix.starts_with(b">") {
+            return SequenceFileType::Fasta;
+        }
+        if prefix.starts_with(b"@") {
+            return SequenceFileType::Fastq;
+        }
+        if prefix.starts_with(b"LOCUS") {
+            return SequenceFileType::Genbank;
+        }
+        if prefix.starts_with(b"ID   ") {
+            return SequenceFileType::Embl;
+        }
+
+        return SequenceFileType::RawSequence;
+    }
 }
 
+/// A named sequence of the input.
+pub struct SequenceRecord {
+    pub id: String,
+    /// Empty if the bases are packed
+    pub sequence: String,
+    /// The bases packed with two bits each, set instead of the sequence by readers of large sequences,
+    /// see `IndexedFasta::into_packed_records`
+    pub packed: Option<PackedSequence>,
+    /// Location of the sequence within the sequence named by the id, if it is only a part of it
+    pub offset: Option<RecordOffset>,
+}
+
+impl SequenceRecord {
+    /// Number of bases of the record
+    pub fn len(&self) -> usize {
+        return match &self.packed {
+            Some(packed) => packed.len(),
+            None => self.sequence.len(),
+        };
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.len() == 0;
+    }
+
+    /// The bases of the record, packed bases are unpacked.
+    pub fn bases(&self) -> Cow<'_, [u8]> {
+        return match &self.packed {
+            Some(packed) => Cow::Owned(
+                (0..packed.len())
+                    .map(|position| packed.base(position))
+                    .collect(),
+            ),
+            None => Cow::Borrowed(self.sequence.as_bytes()),
+        };
+    }
+
+    /// The record with its bases unpacked into the sequence, e.g. to apply variants to it.
+    pub fn into_unpacked(mut self) -> Self {
+        if let Some(packed) = self.packed.take() {
+            self.sequence = (0..packed.len())
+                .map(|position| packed.base(position) as char)
+                .collect();
+        }
+
+        return self;
+    }
+}
+
+/// Location of a record within a longer sequence, e.g. a region of a chromosome.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct RecordOffset {
+    /// Position of the first base of the record in the longer sequence
+    pub start: usize,
+    /// Number of bases of the longer sequence
+    pub source_len: usize,
+}
+
+impl RecordOffset {
+    /// Distance between the positions on a strand of the record and the same strand of the longer sequence.
+    /// On the reverse strand the record starts at the bases following it on the forward strand.
+    /// * `direction` - The strand of the positions
+    /// * `len` - Number of bases of the record
+    pub fn strand_offset(&self, direction: Direction, len: usize) -> usize {
+        return match direction {
+            Direction::FORWARD => self.start,
+            Direction::REVERSE => self.source_len - self.start - len,
+        };
+    }
+}
+
+/// Records of the input, read one at a time while iterating.
+pub type SequenceRecords = Box<dyn Iterator<Item = Result<SequenceRecord, OrfFinderError>> + Send>;
+
+/// Reads all sequences of the input at once, see `read_records` to stream large inputs.
 pub fn read_input(
-    input: Box<dyn std::io::Read + Send + Sync>,
+    input: Box<dyn std::io::Read + Send>,
     file_type: SequenceFileType,
-) -> Result<Vec<String>, Box<dyn std::error::Error>> {
-    let sequences = match file_type {
-        SequenceFileType::RawSequence => read_fasta_normal(input)?,
-        SequenceFileType::Fasta => read_fasta(input)?,
-    };
+) -> Result<Vec<String>, OrfFinderError> {
+    let sequences = read_records(input, file_type)
+        .map(|record| record.map(|record| record.sequence))
+        .collect::<Result<_, _>>()?;
+
     return Ok(sequences);
 }
 
+/// Streams the records of the input, only the record currently returned is held in memory.
+/// Compressed input is decompressed transparently. A raw sequence is a single record that is read completely.
+/// With `SequenceFileType::Auto` the file type is detected from the first bytes of the decompressed input.
+pub fn read_records(
+    input: Box<dyn std::io::Read + Send>,
+    file_type: SequenceFileType,
+) -> SequenceRecords {
+    let input = match decompress(input) {
+        Ok(input) => input,
+        Err(err) => {
+            let err =
+                OrfFinderError::InputParse(format!("could not detect the compression: {}", err));
+            return Box::new(std::iter::once(Err(err)));
+        }
+    };
+    let (input, file_type) = match file_type {
+        SequenceFileType::Auto => match detect_file_type(input) {
+            Ok(detected) => detected,
+            Err(err) => return Box::new(std::iter::once(Err(err))),
+        },
+        file_type => (input, file_type),
+    };
+
+    return match file_type {
+        SequenceFileType::RawSequence => Box::new(std::iter::once(read_fasta_normal(input))),
+        SequenceFileType::Fasta => read_fasta(input),
+        SequenceFileType::Fastq => read_fastq(input),
+        SequenceFileType::Genbank => Box::new(FlatFileRecords::new(
+            BufReader::new(input),
+            FlatFileFormat::GenBank,
+        )),
+        SequenceFileType::Embl => Box::new(FlatFileRecords::new(
+            BufReader::new(input),
+            FlatFileFormat::Embl,
+        )),
+        SequenceFileType::TwoBit => read_twobit(input),
+        SequenceFileType::Auto => unreachable!("the file type is detected above"),
+    };
+}
+
+/// Reads the first bytes of the input to detect its file type and returns the input including them.
+fn detect_file_type(
+    mut input: Box<dyn Read + Send>,
+) -> Result<(Box<dyn Read + Send>, SequenceFileType), OrfFinderError> {
+    let mut prefix = Vec::with_capacity(SNIFF_LEN);
+    input
+        .by_ref()
+        .take(SNIFF_LEN as u64)
+        .read_to_end(&mut prefix)
+        .map_err(|err| {
+            OrfFinderError::InputParse(format!("could not detect the file type: {}", err))
+        })?;
+    let file_type = SequenceFileType::detect(&prefix);
+
+    return Ok((Box::new(Cursor::new(prefix).chain(input)), file_type));
+}
+
 fn read_fasta_normal(
-    mut input: Box<dyn std::io::Read + Send + Sync>,
-) -> Result<Vec<String>, Box<dyn std::error::Error>> {
-    let mut string = String::new();
-    input.read_to_string(&mut string)?;
+    mut input: Box<dyn std::io::Read + Send>,
+) -> Result<SequenceRecord, OrfFinderError> {
+    let mut content = Vec::new();
+    input
+        .read_to_end(&mut content)
+        .map_err(|err| OrfFinderError::InputParse(err.to_string()))?;
+
+    return Ok(SequenceRecord {
+        id: DEFAULT_SEQID.to_string(),
+        sequence: parse_raw_sequence(&content)?,
+        packed: None,
+        offset: None,
+    });
+}
+
+/// Extracts the bases of a raw DNA or RNA sequence, including unknown bases (N), converted to uppercase.
+/// Whitespace and digits are skipped, so that line breaks and position numbers as in a GenBank ORIGIN section are allowed.
+/// Any other character is reported with its line and column.
+pub fn parse_raw_sequence(content: &[u8]) -> Result<String, OrfFinderError> {
+    let mut sequence = String::with_capacity(content.len());
+    let mut line = 1;
+    let mut column = 0;
+    for &character in content {
+        column += 1;
+        match character {
+            b'\n' => {
+                line += 1;
+                column = 0;
+            }
+            b'A' | b'C' | b'G' | b'T' | b'U' | b'N' | b'a' | b'c' | b'g' | b't' | b'u' | b'n' => {
+                sequence.push(character.to_ascii_uppercase() as char);
+            }
+            character if character.is_ascii_whitespace() || character.is_ascii_digit() => {}
+            character => {
+                return Err(OrfFinderError::InputParse(format!(
+                    "raw sequence contains the invalid character {:?} at line {}, column {}",
+                    character as char, line, column
+                )));
+            }
+        }
+    }
+
+    return Ok(sequence);
+}
+
+/// The record of an entry of a FASTA or FASTQ file, its bases converted to uppercase.
+/// Entries without an identifier are named after their position.
+/// * `label` - The file format, e.g. `FASTA`, used in error messages
+/// * `index` - Position of the entry in the file, starting at 0
+/// * `id` - Identifier of the entry
+/// * `seq` - Bases of the entry
+fn sequence_record(
+    label: &str,
+    index: usize,
+    id: &str,
+    seq: &[u8],
+) -> Result<SequenceRecord, OrfFinderError> {
+    let sequence = String::from_utf8(seq.to_ascii_uppercase()).map_err(|_| {
+        OrfFinderError::InputParse(format!(
+            "{} record {} ({}) is not valid UTF-8",
+            label,
+            index + 1,
+            id
+        ))
+    })?;
+    let id = match id {
+        "" => format!("{}_{}", DEFAULT_SEQID, index + 1),
+        id => id.to_string(),
+    };
 
-    return Ok(vec![string]);
+    return Ok(SequenceRecord {
+        id: id,
+        sequence: sequence,
+        packed: None,
+        offset: None,
+    });
 }
 
-fn read_fasta(
-    input: Box<dyn std::io::Read + Send + Sync>,
-) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+fn read_fasta(input: Box<dyn std::io::Read + Send>) -> SequenceRecords {
     let fasta_reader = fasta::Reader::new(input);
 
-    let mut sequences = Vec::new();
-    for record in fasta_reader.records() {
-        let fasta_entry = record.unwrap().seq().to_vec();
-        let sequence = String::from_utf8(fasta_entry)?;
-        sequences.push(sequence);
+    let records = fasta_reader.records().enumerate().map(|(index, record)| {
+        let record = record.map_err(|err| {
+            OrfFinderError::InputParse(format!("FASTA record {}: {}", index + 1, err))
+        })?;
+        return sequence_record("FASTA", index, record.id(), record.seq());
+    });
+
+    return Box::new(records);
+}
+
+fn read_fastq(input: Box<dyn std::io::Read + Send>) -> SequenceRecords {
+    let fastq_reader = fastq::Reader::new(input);
+
+    let records = fastq_reader.records().enumerate().map(|(index, record)| {
+        let record = record.map_err(|err| {
+            OrfFinderError::InputParse(format!("FASTQ record {}: {}", index + 1, err))
+        })?;
+        return sequence_record("FASTQ", index, record.id(), record.seq());
+    });
+
+    return Box::new(records);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read};
+
+    use super::{parse_raw_sequence, read_records, SequenceFileType};
+
+    /// Fails once the reader passes the first record, so that only streamed reads succeed
+    struct TruncatedReader {
+        data: Cursor<Vec<u8>>,
     }
 
-    return Ok(sequences);
+    impl Read for TruncatedReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let read = self.data.read(&mut buf[..1])?;
+            if read == 0 {
+                return Err(std::io::Error::other("truncated input"));
+            }
+            return Ok(read);
+        }
+    }
+
+    #[test]
+    fn fasta_records_are_streamed() {
+        let input = b">first\nACGT\nACGT\n>second\nTTTT\n>third\nAC".to_vec();
+        let reader = TruncatedReader {
+            data: Cursor::new(input),
+        };
+
+        let mut records = read_records(Box::new(reader), SequenceFileType::Fasta);
+        let first = records.next().unwrap().unwrap();
+        assert_eq!(first.id, "first");
+        assert_eq!(first.sequence, "ACGTACGT");
+        let second = records.next().unwrap().unwrap();
+        assert_eq!(second.id, "second");
+        assert!(records.next().unwrap().is_err());
+    }
+
+    #[test]
+    fn raw_sequence_skips_whitespace_and_digits() {
+        let content = b"        1 atgaaa tag\r\n       10 CCC nnNN\n\n";
+        assert_eq!(parse_raw_sequence(content).unwrap(), "ATGAAATAGCCCNNNN");
+
+        let err = parse_raw_sequence(b"ATG\nAAXTAG").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "could not read the sequence input: raw sequence contains the invalid character 'X' at line 2, column 3"
+        );
+    }
+
+    #[test]
+    fn detects_file_types() {
+        assert!(SequenceFileType::detect(b">seq\nACGT") == SequenceFileType::Fasta);
+        assert!(SequenceFileType::detect(b"\n@read\nACGT\n+\nIIII") == SequenceFileType::Fastq);
+        assert!(SequenceFileType::detect(b"LOCUS       NC_000001") == SequenceFileType::Genbank);
+        assert!(SequenceFileType::detect(b"ID   X56734; SV 1;") == SequenceFileType::Embl);
+        assert!(SequenceFileType::detect(&[0x43, 0x27, 0x41, 0x1a]) == SequenceFileType::TwoBit);
+        assert!(SequenceFileType::detect(b"ACGTACGT") == SequenceFileType::RawSequence);
+        assert!(SequenceFileType::detect(b"") == SequenceFileType::RawSequence);
+    }
+
+    #[test]
+    fn auto_detection_keeps_the_whole_input() {
+        let input = b"@first\nACGT\n+\nIIII\n@second\nTTAA\n+\nIIII\n".to_vec();
+
+        let records: Vec<_> = read_records(Box::new(Cursor::new(input)), SequenceFileType::Auto)
+            .map(|record| record.unwrap())
+            .collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].id, "first");
+        assert_eq!(records[0].sequence, "ACGT");
+        assert_eq!(records[1].sequence, "TTAA");
+
+        let records: Vec<_> = read_records(
+            Box::new(Cursor::new(b"ATGAAATAG".to_vec())),
+            SequenceFileType::Auto,
+        )
+        .map(|record| record.unwrap())
+        .collect();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].sequence, "ATGAAATAG");
+    }
+
+    #[test]
+    fn converts_fasta_and_fastq_bases_to_uppercase() {
+        let fastq = b"@read\natgNnAAuag\n+\nIIIIIIIIII\n".to_vec();
+        let records: Vec<_> = read_records(Box::new(Cursor::new(fastq)), SequenceFileType::Fastq)
+            .map(|record| record.unwrap())
+            .collect();
+        assert_eq!(records[0].sequence, "ATGNNAAUAG");
+
+        let fasta = b">seq\natgaaaTAG\n".to_vec();
+        let records: Vec<_> = read_records(Box::new(Cursor::new(fasta)), SequenceFileType::Fasta)
+            .map(|record| record.unwrap())
+            .collect();
+        assert_eq!(records[0].sequence, "ATGAAATAG");
+    }
 }
@@ -0,0 +1,191 @@
+use std::io::BufRead;
+
+use crate::{error::error::OrfFinderError, finder::orf_search::DEFAULT_SEQID};
+
+use super::filehandler::SequenceRecord;
+
+/// Annotated flat file formats, each record consists of header lines, a sequence section and a `//` line.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum FlatFileFormat {
+    GenBank,
+    Embl,
+}
+
+impl FlatFileFormat {
+    fn name(&self) -> &'static str {
+        return match self {
+            FlatFileFormat::GenBank => "GenBank",
+            FlatFileFormat::Embl => "EMBL",
+        };
+    }
+}
+
+/// Streams the records of a GenBank or EMBL file, named after their accession.
+/// Sequence lines are stripped of their positions and whitespace and converted to uppercase.
+pub struct FlatFileRecords<R: BufRead> {
+    reader: R,
+    format: FlatFileFormat,
+    index: usize,
+    done: bool,
+}
+
+impl<R: BufRead> FlatFileRecords<R> {
+    pub fn new(reader: R, format: FlatFileFormat) -> Self {
+        return FlatFileRecords {
+            reader: reader,
+            format: format,
+            index: 0,
+            done: false,
+        };
+    }
+
+    fn read_record(&mut self) -> Result<Option<SequenceRecord>, OrfFinderError> {
+        let mut line = String::new();
+        let mut has_content = false;
+        let mut name = None;
+        let mut accession = None;
+        let mut in_sequence = false;
+        let mut sequence = String::new();
+
+        loop {
+            line.clear();
+            let read = self.reader.read_line(&mut line).map_err(|err| {
+                OrfFinderError::InputParse(format!(
+                    "{} record {}: {}",
+                    self.format.name(),
+                    self.index + 1,
+                    err
+                ))
+            })?;
+            if read == 0 {
+                break;
+            }
+
+            let content = line.trim_end();
+            has_content |= !content.is_empty();
+            if content == "//" {
+                let id = accession
+                    .or(name)
+                    .unwrap_or_else(|| format!("{}_{}", DEFAULT_SEQID, self.index + 1));
+                return Ok(Some(SequenceRecord {
+                    id: id,
+                    sequence: sequence,
+                    packed: None,
+                    offset: None,
+                }));
+            }
+
+            if in_sequence {
+                let bases = content
+                    .chars()
+                    .filter(|base| base.is_ascii_alphabetic())
+                    .map(|base| base.to_ascii_uppercase());
+                sequence.extend(bases);
+                continue;
+            }
+
+            let mut fields = content.split_whitespace();
+            let keyword = fields.next().unwrap_or_default();
+            let value = fields
+                .next()
+                .map(|value| value.trim_end_matches(';').to_string());
+            match (self.format, keyword) {
+                (FlatFileFormat::GenBank, "LOCUS") | (FlatFileFormat::Embl, "ID") => name = value,
+                (FlatFileFormat::GenBank, "ACCESSION") | (FlatFileFormat::Embl, "AC") => {
+                    // Only the primary accession of the first accession line is used
+                    accession = accession.or(value);
+                }
+                (FlatFileFormat::GenBank, "ORIGIN") | (FlatFileFormat::Embl, "SQ") => {
+                    in_sequence = true;
+                }
+                _ => {}
+            }
+        }
+
+        if has_content {
+            return Err(OrfFinderError::InputParse(format!(
+                "{} record {} is not terminated by //",
+                self.format.name(),
+                self.index + 1
+            )));
+        }
+
+        return Ok(None);
+    }
+}
+
+impl<R: BufRead> Iterator for FlatFileRecords<R> {
+    type Item = Result<SequenceRecord, OrfFinderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let record = self.read_record();
+        self.index += 1;
+        // Reading stops at the end of the input or at the first malformed record
+        self.done = !matches!(record, Ok(Some(_)));
+
+        return record.transpose();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FlatFileFormat, FlatFileRecords};
+
+    #[test]
+    fn reads_genbank_records() {
+        let genbank = "LOCUS       NC_000001  12 bp    DNA     linear   BCT 01-JAN-2000
+DEFINITION  Test.
+ACCESSION   NC_000001 NC_000002
+FEATURES             Location/Qualifiers
+ORIGIN
+        1 atgaaa tag
+       10 ccc
+//
+LOCUS       second  3 bp    DNA     linear   BCT 01-JAN-2000
+ORIGIN
+        1 ggg
+//
+";
+        let records: Vec<_> = FlatFileRecords::new(genbank.as_bytes(), FlatFileFormat::GenBank)
+            .map(|record| record.unwrap())
+            .collect();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].id, "NC_000001");
+        assert_eq!(records[0].sequence, "ATGAAATAGCCC");
+        assert_eq!(records[1].id, "second");
+        assert_eq!(records[1].sequence, "GGG");
+    }
+
+    #[test]
+    fn reads_embl_records() {
+        let embl = "ID   X56734; SV 1; linear; mRNA; STD; PLN; 12 BP.
+XX
+AC   X56734; S46826;
+XX
+SQ   Sequence 12 BP; 4 A; 3 C; 3 G; 2 T; other;
+     atgaaataga cc                                                        12
+//
+";
+        let records: Vec<_> = FlatFileRecords::new(embl.as_bytes(), FlatFileFormat::Embl)
+            .map(|record| record.unwrap())
+            .collect();
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].id, "X56734");
+        assert_eq!(records[0].sequence, "ATGAAATAGACC");
+    }
+
+    #[test]
+    fn unterminated_record_is_an_error() {
+        let genbank = "LOCUS       test\nORIGIN\n        1 atg\n";
+        let mut records = FlatFileRecords::new(genbank.as_bytes(), FlatFileFormat::GenBank);
+
+        assert!(records.next().unwrap().is_err());
+        assert!(records.next().is_none());
+    }
+}
@@ -0,0 +1,390 @@
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+use memmap2::Mmap;
+
+use crate::{error::error::OrfFinderError, models::packed_sequence::PackedSequence};
+
+use super::{
+    decompress::Compression,
+    filehandler::{RecordOffset, SequenceRecord, SequenceRecords},
+    region::Region,
+};
+
+/// A line of a FASTA index as written by `samtools faidx`.
+pub struct FastaIndexEntry {
+    pub name: String,
+    /// Number of bases of the sequence
+    pub length: usize,
+    /// Byte offset of the first base in the FASTA file
+    offset: usize,
+    /// Number of bases per line
+    line_bases: usize,
+    /// Number of bytes per line, including the line terminator
+    line_width: usize,
+}
+
+impl FastaIndexEntry {
+    fn parse(line: &str, line_number: usize) -> Result<Self, OrfFinderError> {
+        let fields: Vec<&str> = line.split('\t').collect();
+        let number = |index: usize| -> Result<usize, OrfFinderError> {
+            return fields
+                .get(index)
+                .and_then(|field| field.trim().parse().ok())
+                .ok_or_else(|| {
+                    OrfFinderError::InputParse(format!(
+                        "line {} of the FASTA index is malformed",
+                        line_number
+                    ))
+                });
+        };
+
+        return Ok(FastaIndexEntry {
+            name: fields[0].to_string(),
+            length: number(1)?,
+            offset: number(2)?,
+            line_bases: number(3)?,
+            line_width: number(4)?,
+        });
+    }
+
+    /// Byte offset of the base in the FASTA file
+    fn byte_offset(&self, position: usize) -> usize {
+        return self.offset
+            + (position / self.line_bases) * self.line_width
+            + position % self.line_bases;
+    }
+}
+
+/// A FASTA file accessed through its index, the file is memory-mapped instead of read.
+/// Sequences or parts of them are copied out of the mapping on request, so only the pages
+/// of the requested bases are loaded.
+pub struct IndexedFasta {
+    mmap: Mmap,
+    entries: Vec<FastaIndexEntry>,
+}
+
+impl IndexedFasta {
+    /// Maps the FASTA file and reads its index from `<path>.fai`.
+    /// Fails if the index is missing or does not fit the file, e.g. because the file is compressed.
+    pub fn open(path: &Path) -> Result<Self, OrfFinderError> {
+        let io_error = |path: &Path, err: std::io::Error| {
+            OrfFinderError::InputParse(format!("{}: {}", path.display(), err))
+        };
+
+        let mut index_path = PathBuf::from(path).into_os_string();
+        index_path.push(".fai");
+        let index_path = PathBuf::from(index_path);
+        let index =
+            std::fs::read_to_string(&index_path).map_err(|err| io_error(&index_path, err))?;
+        let entries = index
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| FastaIndexEntry::parse(line, index + 1))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let file = File::open(path).map_err(|err| io_error(path, err))?;
+        // SAFETY: The mapping is only read, the FASTA file must not be modified while it is searched
+        let mmap = unsafe { Mmap::map(&file) }.map_err(|err| io_error(path, err))?;
+        if Compression::detect(&mmap[..mmap.len().min(4)]) != Compression::None {
+            return Err(OrfFinderError::InputParse(format!(
+                "{}: indexed FASTA files have to be uncompressed",
+                path.display()
+            )));
+        }
+
+        for entry in entries.iter().filter(|entry| entry.length > 0) {
+            let fits = entry.line_bases > 0
+                && entry.line_width >= entry.line_bases
+                && entry.byte_offset(entry.length - 1) < mmap.len();
+            if !fits {
+                return Err(OrfFinderError::InputParse(format!(
+                    "{}: the index entry of {} does not fit the FASTA file",
+                    index_path.display(),
+                    entry.name
+                )));
+            }
+        }
+
+        return Ok(IndexedFasta {
+            mmap: mmap,
+            entries: entries,
+        });
+    }
+
+    pub fn entries(&self) -> &[FastaIndexEntry] {
+        return &self.entries;
+    }
+
+    /// The entry of the sequence with the given name.
+    pub fn entry(&self, name: &str) -> Option<&FastaIndexEntry> {
+        return self.entries.iter().find(|entry| entry.name == name);
+    }
+
+    /// The mapped lines holding the bases from start to end of the sequence, without line terminators.
+    fn lines<'a>(
+        &'a self,
+        entry: &'a FastaIndexEntry,
+        start: usize,
+        end: usize,
+    ) -> Result<impl Iterator<Item = &'a [u8]>, OrfFinderError> {
+        if start > end || end > entry.length {
+            return Err(OrfFinderError::InputParse(format!(
+                "{}:{}-{} exceeds the sequence of {} bases",
+                entry.name, start, end, entry.length
+            )));
+        }
+
+        let mut position = start;
+        let lines = std::iter::from_fn(move || {
+            if position >= end {
+                return None;
+            }
+            // The rest of the current line
+            let line_end = end.min((position / entry.line_bases + 1) * entry.line_bases);
+            let byte_offset = entry.byte_offset(position);
+            let line = &self.mmap[byte_offset..byte_offset + line_end - position];
+            position = line_end;
+            return Some(line);
+        });
+
+        return Ok(lines);
+    }
+
+    /// Copies the bases from start to end of the sequence out of the mapping, converted to uppercase.
+    /// RNA is kept, so that its alphabet can be preserved.
+    /// * `entry` - Index entry of the sequence
+    /// * `start` - Position of the first base, starting at 0
+    /// * `end` - Position after the last base, at most the length of the sequence
+    pub fn fetch(
+        &self,
+        entry: &FastaIndexEntry,
+        start: usize,
+        end: usize,
+    ) -> Result<String, OrfFinderError> {
+        let mut sequence = Vec::with_capacity(end.saturating_sub(start));
+        for line in self.lines(entry, start, end)? {
+            sequence.extend(line.iter().map(|base| base.to_ascii_uppercase()));
+        }
+
+        return String::from_utf8(sequence).map_err(|_| {
+            OrfFinderError::InputParse(format!("FASTA record {} is not valid UTF-8", entry.name))
+        });
+    }
+
+    /// Packs the bases from start to end of the sequence straight out of the mapping, without copying them first.
+    /// RNA is packed as DNA. Fails on the first base that is not A, C, G, T, U or N.
+    /// * `entry` - Index entry of the sequence
+    /// * `start` - Position of the first base, starting at 0
+    /// * `end` - Position after the last base, at most the length of the sequence
+    pub fn fetch_packed(
+        &self,
+        entry: &FastaIndexEntry,
+        start: usize,
+        end: usize,
+    ) -> Result<PackedSequence, OrfFinderError> {
+        let mut sequence = PackedSequence::with_capacity(end.saturating_sub(start));
+        for line in self.lines(entry, start, end)? {
+            for base in line {
+                let base = match base.to_ascii_uppercase() {
+                    b'U' => b'T',
+                    base => base,
+                };
+                sequence.push(base)?;
+            }
+        }
+
+        return Ok(sequence);
+    }
+
+    /// The record of the bases from start to end of the sequence, packed or copied out of the mapping.
+    /// The record is not placed within the sequence.
+    fn record(
+        &self,
+        entry: &FastaIndexEntry,
+        start: usize,
+        end: usize,
+        packed: bool,
+    ) -> Result<SequenceRecord, OrfFinderError> {
+        let (sequence, packed) = match packed {
+            true => (String::new(), Some(self.fetch_packed(entry, start, end)?)),
+            false => (self.fetch(entry, start, end)?, None),
+        };
+
+        return Ok(SequenceRecord {
+            id: entry.name.clone(),
+            sequence: sequence,
+            packed: packed,
+            offset: None,
+        });
+    }
+
+    /// Streams all sequences in the order of the index, each one is copied out of the mapping when it is reached.
+    /// * `packed` - Packs the bases of each record straight out of the mapping instead of copying them
+    pub fn into_records(self, packed: bool) -> SequenceRecords {
+        let fasta = self;
+        let records = (0..fasta.entries.len()).map(move |index| {
+            let entry = &fasta.entries[index];
+            return fasta.record(entry, 0, entry.length, packed);
+        });
+
+        return Box::new(records);
+    }
+
+    /// Streams the parts of the sequences within the regions, in the order of the regions.
+    /// Each record is named after its sequence and placed within it by its offset.
+    /// Fails if a region names an unknown sequence or exceeds its sequence.
+    /// * `regions` - The regions to read
+    /// * `packed` - Packs the bases of each record straight out of the mapping instead of copying them
+    pub fn into_region_records(
+        self,
+        regions: Vec<Region>,
+        packed: bool,
+    ) -> Result<SequenceRecords, OrfFinderError> {
+        let mut ranges = Vec::with_capacity(regions.len());
+        for region in regions {
+            let index = self
+                .entries
+                .iter()
+                .position(|entry| entry.name == region.seqid)
+                .ok_or_else(|| {
+                    OrfFinderError::InvalidConfig(format!(
+                        "the region sequence {} is not part of the FASTA index",
+                        region.seqid
+                    ))
+                })?;
+            let length = self.entries[index].length;
+            let end = region.end.unwrap_or(length);
+            if region.start >= end || end > length {
+                return Err(OrfFinderError::InvalidConfig(format!(
+                    "the region {}:{}-{} is empty or exceeds the sequence of {} bases",
+                    region.seqid,
+                    region.start + 1,
+                    end,
+                    length
+                )));
+            }
+            ranges.push((index, region.start, end));
+        }
+
+        let fasta = self;
+        let records = ranges.into_iter().map(move |(index, start, end)| {
+            let entry = &fasta.entries[index];
+            let mut record = fasta.record(entry, start, end, packed)?;
+            record.offset = Some(RecordOffset {
+                start: start,
+                source_len: entry.length,
+            });
+            return Ok(record);
+        });
+
+        return Ok(Box::new(records));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::{
+        find_orfs_in_records,
+        finder::finder_config::FinderConfig,
+        outwriter::outwriter::OutputType,
+        test_utils::{SharedBuffer, TempFile},
+    };
+
+    use super::IndexedFasta;
+
+    /// Writes the FASTA file and its index, both are removed when the returned files are dropped
+    fn write_indexed_fasta(name: &str, fasta: &str, index: &str) -> (TempFile, TempFile) {
+        let fasta = TempFile::new(&format!("{}.fasta", name), fasta);
+        let index = TempFile::at(
+            PathBuf::from(format!("{}.fai", fasta.path().display())),
+            index,
+        );
+
+        return (fasta, index);
+    }
+
+    #[test]
+    fn fetches_sequences_across_lines() {
+        let (file, _index) = write_indexed_fasta(
+            "indexed",
+            ">first\nACGTA\nCCGGT\nTT\n>second\nGGGG\n",
+            "first\t12\t7\t5\t6\nsecond\t4\t30\t4\t5\n",
+        );
+        let fasta = IndexedFasta::open(file.path()).unwrap();
+
+        let first = fasta.entry("first").unwrap();
+        assert_eq!(fasta.fetch(first, 0, 12).unwrap(), "ACGTACCGGTTT");
+        assert_eq!(fasta.fetch(first, 3, 11).unwrap(), "TACCGGTT");
+        assert!(fasta.fetch(first, 3, 13).is_err());
+
+        let packed = fasta.fetch_packed(first, 3, 11).unwrap();
+        let unpacked: Vec<u8> = (0..packed.len()).map(|i| packed.base(i)).collect();
+        assert_eq!(unpacked, b"TACCGGTT");
+
+        let records: Vec<_> = fasta
+            .into_records(true)
+            .map(|record| record.unwrap())
+            .collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].id, "second");
+        assert!(records[1].sequence.is_empty());
+        assert_eq!(records[1].bases().as_ref(), b"GGGG");
+    }
+
+    #[test]
+    fn index_has_to_fit_the_file() {
+        let (file, _index) =
+            write_indexed_fasta("stale_index", ">first\nACGT\n", "first\t400\t7\t4\t5\n");
+
+        assert!(IndexedFasta::open(file.path()).is_err());
+    }
+
+    #[test]
+    fn soft_masked_sequences_are_read_in_each_storage_mode() {
+        let (file, _index) = write_indexed_fasta(
+            "soft_masked",
+            ">first\natgaaACCCT\nAGccc\n",
+            "first\t15\t7\t10\t11\n",
+        );
+        let fasta = IndexedFasta::open(file.path()).unwrap();
+        assert_eq!(
+            fasta.fetch(fasta.entry("first").unwrap(), 0, 15).unwrap(),
+            "ATGAAACCCTAGCCC"
+        );
+
+        for packed in [false, true] {
+            let config = FinderConfig::builder().min_len(3).build().unwrap();
+            let records = IndexedFasta::open(file.path())
+                .unwrap()
+                .into_records(packed);
+            let out_target = SharedBuffer::default();
+            find_orfs_in_records(
+                records,
+                &config,
+                OutputType::TSV,
+                Box::new(out_target.clone()),
+                |_| {
+                    return Ok(vec![]);
+                },
+            )
+            .unwrap();
+
+            let tsv = out_target.contents();
+            let orfs: Vec<(&str, &str, &str)> = tsv
+                .lines()
+                .filter(|line| !line.starts_with('#') && !line.starts_with("seqid"))
+                .map(|line| {
+                    let columns: Vec<&str> = line.split('\t').collect();
+                    return (columns[1], columns[2], columns[3]);
+                })
+                .collect();
+            assert_eq!(orfs, vec![("0", "9", "+")]);
+        }
+    }
+}
@@ -2,17 +2,138 @@ use std::{collections::HashMap, path::PathBuf};
 
 use bio::io::gff;
 
-pub fn parse_mask_gff3_file(
-    path: PathBuf,
-) -> Result<HashMap<u64, u64>, Box<dyn std::error::Error>> {
-    let gff3_file = std::fs::File::open(path.as_path())?;
+use crate::error::error::OrfFinderError;
+
+use super::decompress::decompress;
+
+/// Masked areas keyed by the identifier of their sequence.
+/// The areas of each sequence are 1-based, inclusive, sorted and do not overlap.
+pub type MaskedAreas = HashMap<String, Vec<(u64, u64)>>;
+
+/// Reads the masked areas from a GFF3 file, which may be compressed.
+pub fn parse_mask_gff3_file(path: PathBuf) -> Result<MaskedAreas, OrfFinderError> {
+    let gff3_file = std::fs::File::open(path.as_path())
+        .and_then(|file| decompress(Box::new(file)))
+        .map_err(|err| OrfFinderError::MaskParse(format!("{}: {}", path.display(), err)))?;
     let mut gff_reader = gff::Reader::new(gff3_file, gff::GffType::GFF3);
-    let mut mask_starts = HashMap::new();
+    let mut masked_areas = MaskedAreas::new();
 
     for record_result in gff_reader.records() {
-        let record = record_result?;
-        mask_starts.insert(record.start().to_owned(), record.end().to_owned());
+        let record = record_result
+            .map_err(|err| OrfFinderError::MaskParse(format!("{}: {}", path.display(), err)))?;
+        masked_areas
+            .entry(record.seqname().to_string())
+            .or_default()
+            .push((*record.start(), *record.end()));
+    }
+    for areas in masked_areas.values_mut() {
+        merge_areas(areas);
+    }
+
+    return Ok(masked_areas);
+}
+
+/// Sorts the areas and merges overlapping and adjacent ones, so that their ends are sorted as well.
+pub fn merge_areas(areas: &mut Vec<(u64, u64)>) {
+    areas.sort_unstable();
+
+    let mut merged: Vec<(u64, u64)> = Vec::with_capacity(areas.len());
+    for &(start, end) in areas.iter() {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 + 1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+
+    *areas = merged;
+}
+
+/// Indicates if any of the merged areas overlaps the bases from `start` up to `end`, starting at 0.
+pub fn overlaps_masked_area(areas: &[(u64, u64)], start: u64, end: u64) -> bool {
+    // The first area ending after the start is the only candidate, all following areas start later
+    let candidate = areas.partition_point(|&(_, area_end)| area_end <= start);
+    return areas
+        .get(candidate)
+        .is_some_and(|&(area_start, _)| area_start <= end);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        datahandler::filehandler::SequenceRecord,
+        find_orfs_in_records,
+        finder::finder_config::FinderConfig,
+        outwriter::outwriter::OutputType,
+        test_utils::{SharedBuffer, TempFile},
+    };
+
+    use super::{overlaps_masked_area, parse_mask_gff3_file};
+
+    #[test]
+    fn reads_areas_of_each_sequence() {
+        let file = TempFile::new(
+            "mask.gff3",
+            "##gff-version 3\n\
+             c1\t.\tregion\t20\t30\t.\t+\t.\tID=m1\n\
+             c1\t.\tregion\t1\t5\t.\t+\t.\tID=m2\n\
+             c1\t.\tregion\t1\t8\t.\t+\t.\tID=m3\n\
+             c1\t.\tregion\t25\t40\t.\t+\t.\tID=m4\n\
+             c2\t.\tregion\t100\t100\t.\t+\t.\tID=m5\n",
+        );
+
+        let masked_areas = parse_mask_gff3_file(file.path().to_path_buf()).unwrap();
+        assert_eq!(masked_areas["c1"], vec![(1, 8), (20, 40)]);
+        assert_eq!(masked_areas["c2"], vec![(100, 100)]);
+
+        let areas = &masked_areas["c1"];
+        assert!(overlaps_masked_area(areas, 0, 3));
+        assert!(overlaps_masked_area(areas, 7, 10));
+        assert!(!overlaps_masked_area(areas, 8, 18));
+        assert!(overlaps_masked_area(areas, 18, 20));
+        assert!(!overlaps_masked_area(areas, 40, 50));
     }
 
-    return Ok(mask_starts);
+    #[test]
+    fn masks_only_orfs_of_their_sequence() {
+        let file = TempFile::new(
+            "mask.gff3",
+            "##gff-version 3\nc1\t.\tregion\t1\t5\t.\t+\t.\tID=m1\n",
+        );
+        let config = FinderConfig::builder()
+            .min_len(3)
+            .mask_file(Some(file.path().to_path_buf()))
+            .build()
+            .unwrap();
+        let records = ["c1", "c2"].map(|id| {
+            return Ok(SequenceRecord {
+                id: id.to_string(),
+                sequence: "ATGAAACCCGGGTTTCCCTAG".to_string(),
+                packed: None,
+                offset: None,
+            });
+        });
+
+        let out_target = SharedBuffer::default();
+        find_orfs_in_records(
+            records,
+            &config,
+            OutputType::TSV,
+            Box::new(out_target.clone()),
+            |_| {
+                return Ok(vec![]);
+            },
+        )
+        .unwrap();
+
+        let tsv = out_target.contents();
+        let orfs: Vec<(&str, &str, &str)> = tsv
+            .lines()
+            .filter(|line| !line.starts_with('#') && !line.starts_with("seqid"))
+            .map(|line| {
+                let columns: Vec<&str> = line.split('\t').collect();
+                return (columns[0], columns[1], columns[2]);
+            })
+            .collect();
+        assert_eq!(orfs, vec![("c2", "0", "18")]);
+    }
 }
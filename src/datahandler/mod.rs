@@ -1,3 +1,11 @@
+pub mod cds_annotation;
+pub mod decompress;
 pub mod filehandler;
+pub mod flatfile;
+pub mod indexed_fasta;
 pub mod mask_file;
+pub mod region;
 pub mod trans_table;
+pub mod transcript_structure;
+pub mod twobit;
+pub mod vcf;
@@ -0,0 +1,137 @@
+use std::{path::PathBuf, str::FromStr};
+
+use crate::error::error::OrfFinderError;
+
+/// A part of a sequence, positions start at 0 and the end is exclusive.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Region {
+    pub seqid: String,
+    pub start: usize,
+    /// Position after the last base, the region extends to the end of the sequence if none
+    pub end: Option<usize>,
+}
+
+impl FromStr for Region {
+    type Err = OrfFinderError;
+
+    /// Parses a region in the samtools notation `chr`, `chr:start` or `chr:start-end`.
+    /// Positions start at 1 and the end is inclusive, thousands separators are allowed.
+    fn from_str(region: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            OrfFinderError::InvalidConfig(format!(
+                "invalid region {}, expected chr, chr:start or chr:start-end",
+                region
+            ))
+        };
+        let position = |position: &str| -> Result<usize, OrfFinderError> {
+            return position.replace(',', "").parse().map_err(|_| invalid());
+        };
+
+        let (seqid, range) = match region.rsplit_once(':') {
+            Some((seqid, range)) => (seqid, Some(range)),
+            None => (region, None),
+        };
+        if seqid.is_empty() {
+            return Err(invalid());
+        }
+
+        let (start, end) = match range.map(|range| range.split_once('-')) {
+            None => (0, None),
+            Some(None) => (position(range.unwrap())?, None),
+            Some(Some((start, end))) => (position(start)?, Some(position(end)?)),
+        };
+        if start == 0 && range.is_some() || end.is_some_and(|end| end < start) {
+            return Err(invalid());
+        }
+
+        return Ok(Region {
+            seqid: seqid.to_string(),
+            start: start.saturating_sub(1),
+            end: end,
+        });
+    }
+}
+
+/// Reads the regions of a BED file, only the first three columns are used.
+/// Header lines starting with `#`, `track` or `browser` are skipped.
+pub fn parse_bed_file(path: PathBuf) -> Result<Vec<Region>, OrfFinderError> {
+    let bed_error =
+        |message: String| OrfFinderError::InvalidConfig(format!("{}: {}", path.display(), message));
+
+    let content = std::fs::read_to_string(&path).map_err(|err| bed_error(err.to_string()))?;
+    let mut regions = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim_end();
+        if line.is_empty()
+            || line.starts_with('#')
+            || line.starts_with("track")
+            || line.starts_with("browser")
+        {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split('\t').collect();
+        let position = |column: usize| -> Result<usize, OrfFinderError> {
+            return fields
+                .get(column)
+                .and_then(|field| field.trim().parse().ok())
+                .ok_or_else(|| bed_error(format!("line {} is malformed", index + 1)));
+        };
+        let (start, end) = (position(1)?, position(2)?);
+        if end < start {
+            return Err(bed_error(format!(
+                "line {} ends before it starts",
+                index + 1
+            )));
+        }
+
+        regions.push(Region {
+            seqid: fields[0].to_string(),
+            start: start,
+            end: Some(end),
+        });
+    }
+
+    return Ok(regions);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::TempFile;
+
+    use super::{parse_bed_file, Region};
+
+    #[test]
+    fn parses_samtools_regions() {
+        let region: Region = "chr1:1,001-2,000".parse().unwrap();
+        assert_eq!(region.seqid, "chr1");
+        assert_eq!(region.start, 1000);
+        assert_eq!(region.end, Some(2000));
+
+        let region: Region = "chr2:500".parse().unwrap();
+        assert_eq!((region.start, region.end), (499, None));
+        let region: Region = "chr3".parse().unwrap();
+        assert_eq!((region.start, region.end), (0, None));
+        let region: Region = "HLA-A*01:01:1-10".parse().unwrap();
+        assert_eq!(region.seqid, "HLA-A*01:01");
+
+        assert!("chr1:0-10".parse::<Region>().is_err());
+        assert!("chr1:20-10".parse::<Region>().is_err());
+        assert!("chr1:a-10".parse::<Region>().is_err());
+        assert!(":1-10".parse::<Region>().is_err());
+    }
+
+    #[test]
+    fn parses_bed_regions() {
+        let file = TempFile::new(
+            "regions.bed",
+            "track name=test\n# comment\nchr1\t0\t100\tname\nchr2\t50\t60\n",
+        );
+
+        let regions = parse_bed_file(file.path().to_path_buf()).unwrap();
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].start, 0);
+        assert_eq!(regions[1].seqid, "chr2");
+        assert_eq!(regions[1].end, Some(60));
+    }
+}
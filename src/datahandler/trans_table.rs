@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::error::error::OrfFinderError;
+
 const TRANS_TABLE_11: &'static [u8] =
     include_bytes!("../../resources/codon_tables/trans_table_11.json");
 
@@ -9,23 +11,146 @@ pub struct TranslationalTable {
     pub stop_codons: Vec<String>,
 }
 
+/// Classification of a codon within a translational table.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum CodonClass {
+    Sense,
+    Start,
+    Stop,
+}
+
+/// 2-bit code of each base, A=0, C=1, G=2, T=3, all other bytes are marked as invalid
+const BASE_CODES: [u8; 256] = {
+    let mut codes = [INVALID_BASE; 256];
+    codes[b'A' as usize] = 0;
+    codes[b'C' as usize] = 1;
+    codes[b'G' as usize] = 2;
+    codes[b'T' as usize] = 3;
+    codes[b'a' as usize] = 0;
+    codes[b'c' as usize] = 1;
+    codes[b'g' as usize] = 2;
+    codes[b't' as usize] = 3;
+    codes
+};
+const INVALID_BASE: u8 = 4;
+
+/// Index of a codon among all 64 codons based on the 2-bit code of its bases,
+/// none if the codon contains ambiguous bases.
+#[inline(always)]
+pub fn codon_index(codon: &[u8]) -> Option<usize> {
+    let first = BASE_CODES[codon[0] as usize];
+    let second = BASE_CODES[codon[1] as usize];
+    let third = BASE_CODES[codon[2] as usize];
+    if (first | second | third) & INVALID_BASE != 0 {
+        return None;
+    }
+
+    return Some(((first as usize) << 4) | ((second as usize) << 2) | third as usize);
+}
+
+/// A translational table compiled into a lookup table, classifying each codon in constant time.
+#[derive(Clone, Debug)]
+pub struct CodonLookup {
+    classes: [CodonClass; 64],
+}
+
+impl CodonLookup {
+    pub fn new(table: &TranslationalTable) -> Self {
+        let mut classes = [CodonClass::Sense; 64];
+        for codon in table.start_codons.iter() {
+            if let Some(index) = codon_index(codon.as_bytes()) {
+                classes[index] = CodonClass::Start;
+            }
+        }
+        for codon in table.stop_codons.iter() {
+            if let Some(index) = codon_index(codon.as_bytes()) {
+                classes[index] = CodonClass::Stop;
+            }
+        }
+
+        return CodonLookup { classes: classes };
+    }
+
+    /// Classifies the first three bases of `codon`, codons with ambiguous bases are sense codons.
+    #[inline(always)]
+    pub fn classify(&self, codon: &[u8]) -> CodonClass {
+        return match codon_index(codon) {
+            Some(index) => self.classes[index],
+            None => CodonClass::Sense,
+        };
+    }
+}
+
+/// Amino acids of the standard genetic code shared by table 11, ordered by `codon_index`
+const STANDARD_AMINO_ACIDS: &[u8; 64] =
+    b"KNKNTTTTRSRSIIMIQHQHPPPPRRRRLLLLEDEDAAAAGGGGVVVV*Y*YSSSS*CWCLFLF";
+
+/// Translates the sequence codon by codon, stop codons are translated to `*` and ambiguous codons to `X`.
+/// The first codon is the start codon, which is translated to methionine regardless of the codon.
+pub fn translate(sequence: &[u8]) -> String {
+    return sequence
+        .chunks_exact(3)
+        .enumerate()
+        .map(|(index, codon)| match (index, codon_index(codon)) {
+            (0, _) => 'M',
+            (_, Some(codon_index)) => STANDARD_AMINO_ACIDS[codon_index] as char,
+            (_, None) => 'X',
+        })
+        .collect();
+}
+
 pub fn parse_translational_table(
     trans_table_number: u8,
-) -> Result<Option<TranslationalTable>, Box<dyn std::error::Error + Send + Sync>> {
-    let codon_translation_table: TranslationalTable = match trans_table_number {
-        11 => serde_json::from_slice(TRANS_TABLE_11)?,
-        _ => return Ok(None),
+) -> Result<TranslationalTable, OrfFinderError> {
+    let table_json = match trans_table_number {
+        11 => TRANS_TABLE_11,
+        _ => return Err(OrfFinderError::UnknownTable(trans_table_number)),
     };
+    let codon_translation_table: TranslationalTable =
+        serde_json::from_slice(table_json).map_err(|err| {
+            OrfFinderError::InvalidConfig(format!(
+                "translational table {} is malformed: {}",
+                trans_table_number, err
+            ))
+        })?;
 
-    return Ok(Some(codon_translation_table));
+    return Ok(codon_translation_table);
 }
 
 #[cfg(test)]
 mod tests {
-    use super::parse_translational_table;
+    use crate::error::error::OrfFinderError;
+
+    use super::{parse_translational_table, translate, CodonClass, CodonLookup};
 
     #[test]
     fn test_parse_translational_table_11() {
-        parse_translational_table(11).unwrap().unwrap();
+        parse_translational_table(11).unwrap();
+    }
+
+    #[test]
+    fn test_parse_unknown_translational_table() {
+        assert!(matches!(
+            parse_translational_table(2),
+            Err(OrfFinderError::UnknownTable(2))
+        ));
+    }
+
+    #[test]
+    fn test_codon_lookup_table_11() {
+        let table = parse_translational_table(11).unwrap();
+        let lookup = CodonLookup::new(&table);
+
+        assert_eq!(lookup.classify(b"ATG"), CodonClass::Start);
+        assert_eq!(lookup.classify(b"gtg"), CodonClass::Start);
+        assert_eq!(lookup.classify(b"TAA"), CodonClass::Stop);
+        assert_eq!(lookup.classify(b"GCC"), CodonClass::Sense);
+        assert_eq!(lookup.classify(b"TNA"), CodonClass::Sense);
+    }
+
+    #[test]
+    fn translates_standard_code() {
+        assert_eq!(translate(b"GTGAAATGGTTTGCNTAGCC"), "MKWFX*");
+        assert_eq!(translate(b"ATGCCCGGGTGA"), "MPG*");
     }
 }
@@ -0,0 +1,239 @@
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader},
+    path::PathBuf,
+};
+
+use bio::alphabets::dna::revcomp;
+
+use crate::{error::error::OrfFinderError, models::models::Direction};
+
+use super::decompress::decompress;
+
+/// The exons of a transcript on the genome.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TranscriptStructure {
+    pub id: String,
+    /// Name of the genome sequence the transcript is located on
+    pub seqid: String,
+    pub direction: Direction,
+    /// Start and end of the exons in ascending order, starting at 0 with an exclusive end
+    pub exons: Vec<(usize, usize)>,
+}
+
+impl TranscriptStructure {
+    /// Number of bases of the spliced transcript
+    pub fn len(&self) -> usize {
+        return self.exons.iter().map(|(start, end)| end - start).sum();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.len() == 0;
+    }
+
+    /// Joins the exons and returns the sequence of the transcript in its direction.
+    /// * `genome_sequence` - The sequence the transcript is located on
+    pub fn splice(&self, genome_sequence: &[u8]) -> Result<String, OrfFinderError> {
+        let mut sequence = Vec::with_capacity(self.len());
+        for (start, end) in &self.exons {
+            let exon = genome_sequence.get(*start..*end).ok_or_else(|| {
+                OrfFinderError::AnnotationParse(format!(
+                    "an exon of {} exceeds the sequence {} of {} bases",
+                    self.id,
+                    self.seqid,
+                    genome_sequence.len()
+                ))
+            })?;
+            sequence.extend(exon.iter().map(|base| base.to_ascii_uppercase()));
+        }
+        if self.direction == Direction::REVERSE {
+            sequence = revcomp(sequence);
+        }
+
+        return Ok(String::from_utf8_lossy(&sequence).to_string());
+    }
+
+    /// Locates a part of the spliced transcript on the genome.
+    /// Returns the parts of the exons it covers in ascending order, starting at 0 with an exclusive end.
+    /// * `start` - Position of the first base on the transcript, in its direction
+    /// * `end` - Position after the last base on the transcript
+    pub fn project(&self, start: usize, end: usize) -> Vec<(usize, usize)> {
+        let mut blocks = Vec::new();
+        let mut transcript_position = 0;
+        let exons: Box<dyn Iterator<Item = &(usize, usize)>> = match self.direction {
+            Direction::FORWARD => Box::new(self.exons.iter()),
+            Direction::REVERSE => Box::new(self.exons.iter().rev()),
+        };
+
+        for (exon_start, exon_end) in exons {
+            let exon_len = exon_end - exon_start;
+            // The overlap with the exon, relative to the start of the exon in the direction of the transcript
+            let from = start.max(transcript_position) - transcript_position;
+            let to = end.min(transcript_position + exon_len);
+            if to > transcript_position && from < exon_len {
+                let to = to - transcript_position;
+                blocks.push(match self.direction {
+                    Direction::FORWARD => (exon_start + from, exon_start + to),
+                    Direction::REVERSE => (exon_end - to, exon_end - from),
+                });
+            }
+            transcript_position += exon_len;
+        }
+        blocks.sort_unstable();
+
+        return blocks;
+    }
+}
+
+/// Reads the exons of the transcripts from a GTF or GFF3 file, which may be compressed.
+/// Exons are assigned to their transcript by the `transcript_id` attribute of GTF or the `Parent` attribute of GFF3.
+/// The transcripts are returned in the order of their first exon.
+pub fn parse_transcript_structure_file(
+    path: PathBuf,
+) -> Result<Vec<TranscriptStructure>, OrfFinderError> {
+    let structure_error = |message: String| {
+        OrfFinderError::AnnotationParse(format!("{}: {}", path.display(), message))
+    };
+
+    let file = std::fs::File::open(path.as_path())
+        .and_then(|file| decompress(Box::new(file)))
+        .map_err(|err| structure_error(err.to_string()))?;
+    let mut transcripts: Vec<TranscriptStructure> = Vec::new();
+    let mut transcript_indices: HashMap<String, usize> = HashMap::new();
+
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|err| structure_error(err.to_string()))?;
+        // GFF3 files may end with the sequences
+        if line.starts_with("##FASTA") {
+            break;
+        }
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() < 9 {
+            return Err(structure_error(format!(
+                "line {} has less than 9 columns",
+                index + 1
+            )));
+        }
+        if fields[2] != "exon" {
+            continue;
+        }
+
+        let malformed = || structure_error(format!("the exon in line {} is malformed", index + 1));
+        let start: usize = fields[3].parse().map_err(|_| malformed())?;
+        let end: usize = fields[4].parse().map_err(|_| malformed())?;
+        let direction = match fields[6] {
+            "+" => Direction::FORWARD,
+            "-" => Direction::REVERSE,
+            _ => return Err(malformed()),
+        };
+        let transcript_ids = transcript_ids(fields[8]);
+        if transcript_ids.is_empty() {
+            return Err(structure_error(format!(
+                "the exon in line {} names no transcript_id or Parent",
+                index + 1
+            )));
+        }
+        if start == 0 || end < start {
+            return Err(malformed());
+        }
+
+        for transcript_id in transcript_ids {
+            let transcript_index = *transcript_indices
+                .entry(transcript_id.to_string())
+                .or_insert_with(|| {
+                    transcripts.push(TranscriptStructure {
+                        id: transcript_id.to_string(),
+                        seqid: fields[0].to_string(),
+                        direction: direction,
+                        exons: Vec::new(),
+                    });
+                    return transcripts.len() - 1;
+                });
+            let transcript = &mut transcripts[transcript_index];
+            if transcript.seqid != fields[0] || transcript.direction != direction {
+                return Err(structure_error(format!(
+                    "the exons of {} are located on different sequences or strands",
+                    transcript_id
+                )));
+            }
+            transcript.exons.push((start - 1, end));
+        }
+    }
+
+    for transcript in transcripts.iter_mut() {
+        transcript.exons.sort_unstable();
+        let overlapping = transcript
+            .exons
+            .windows(2)
+            .any(|exons| exons[1].0 < exons[0].1);
+        if overlapping {
+            return Err(structure_error(format!(
+                "the exons of {} overlap",
+                transcript.id
+            )));
+        }
+    }
+
+    return Ok(transcripts);
+}
+
+/// The transcripts of an exon from the attributes column of GTF or GFF3.
+/// An exon of GFF3 may be shared by several transcripts.
+fn transcript_ids(attributes: &str) -> Vec<&str> {
+    for attribute in attributes.split(';').map(str::trim) {
+        if let Some(value) = attribute.strip_prefix("transcript_id ") {
+            return vec![value.trim().trim_matches('"')];
+        }
+        if let Some(value) = attribute.strip_prefix("Parent=") {
+            return value.split(',').collect();
+        }
+    }
+
+    return Vec::new();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{models::models::Direction, test_utils::TempFile};
+
+    use super::{parse_transcript_structure_file, TranscriptStructure};
+
+    #[test]
+    fn splices_and_projects_reverse_transcripts() {
+        let transcript = TranscriptStructure {
+            id: "tx1".to_string(),
+            seqid: "chr1".to_string(),
+            direction: Direction::REVERSE,
+            exons: vec![(2, 5), (8, 12)],
+        };
+
+        // The exons GTA and ATTA are joined and reverse complemented
+        assert_eq!(transcript.splice(b"ACGTAGGCATTA").unwrap(), "TAATTAC");
+        assert_eq!(transcript.project(1, 6), vec![(3, 5), (8, 11)]);
+        assert_eq!(transcript.project(0, 3), vec![(9, 12)]);
+        assert!(transcript.splice(b"ACGTA").is_err());
+    }
+
+    #[test]
+    fn reads_exons_of_gtf_and_gff3() {
+        let file = TempFile::new(
+            "structure.gtf",
+            "chr1\tsource\ttranscript\t1\t500\t.\t+\t.\ttranscript_id \"tx1\";\n\
+             chr1\tsource\texon\t301\t500\t.\t+\t.\tgene_id \"g1\"; transcript_id \"tx1\";\n\
+             chr1\tsource\texon\t1\t100\t.\t+\t.\tgene_id \"g1\"; transcript_id \"tx1\";\n\
+             chr2\tsource\texon\t11\t40\t.\t-\t.\tID=exon1;Parent=tx2,tx3\n",
+        );
+
+        let transcripts = parse_transcript_structure_file(file.path().to_path_buf()).unwrap();
+        assert_eq!(transcripts.len(), 3);
+        assert_eq!(transcripts[0].exons, vec![(0, 100), (300, 500)]);
+        assert_eq!(transcripts[0].len(), 300);
+        assert_eq!(transcripts[1].id, "tx2");
+        assert_eq!(transcripts[1].direction, Direction::REVERSE);
+        assert_eq!(transcripts[2].exons, vec![(10, 40)]);
+    }
+}
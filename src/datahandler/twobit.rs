@@ -0,0 +1,325 @@
+use std::{
+    collections::VecDeque,
+    io::{self, Read},
+};
+
+use crate::error::error::OrfFinderError;
+
+use super::filehandler::{SequenceRecord, SequenceRecords};
+
+/// Signature of a UCSC 2bit file, in the byte order of the machine that wrote it
+pub const TWOBIT_SIGNATURE: u32 = 0x1A41_2743;
+/// Bases encoded by the two bit values, from the most significant bits of each byte
+const TWOBIT_BASES: [u8; 4] = [b'T', b'C', b'A', b'G'];
+
+/// Detects a 2bit file from its first four bytes, in either byte order.
+pub fn is_twobit(magic: &[u8]) -> bool {
+    return magic.len() >= 4
+        && (magic[..4] == TWOBIT_SIGNATURE.to_le_bytes()
+            || magic[..4] == TWOBIT_SIGNATURE.to_be_bytes());
+}
+
+/// Streams the sequences of a UCSC 2bit file in the order they are stored.
+/// Blocks of unknown bases are restored as `N`, soft masking is ignored and all bases are uppercase.
+/// As stdin can not be seeked, the sequences have to be stored in the order of the index, as written by faToTwoBit.
+pub fn read_twobit(mut input: Box<dyn Read + Send>) -> SequenceRecords {
+    let mut reader = match TwoBitHeader::read(&mut input) {
+        Ok(header) => TwoBitRecords {
+            input: input,
+            big_endian: header.big_endian,
+            entries: header.entries,
+            position: header.size,
+            done: false,
+        },
+        Err(err) => return Box::new(std::iter::once(Err(err))),
+    };
+    reader
+        .entries
+        .make_contiguous()
+        .sort_by_key(|entry| entry.1);
+
+    return Box::new(reader);
+}
+
+fn parse_error(message: impl std::fmt::Display) -> OrfFinderError {
+    return OrfFinderError::InputParse(format!("2bit: {}", message));
+}
+
+struct TwoBitHeader {
+    big_endian: bool,
+    /// Name and offset of each sequence
+    entries: VecDeque<(String, u64)>,
+    /// Number of bytes of the header and index
+    size: u64,
+}
+
+impl TwoBitHeader {
+    fn read(input: &mut impl Read) -> Result<Self, OrfFinderError> {
+        let mut signature = [0; 4];
+        input.read_exact(&mut signature).map_err(parse_error)?;
+        let big_endian = match signature {
+            signature if signature == TWOBIT_SIGNATURE.to_le_bytes() => false,
+            signature if signature == TWOBIT_SIGNATURE.to_be_bytes() => true,
+            _ => return Err(parse_error("invalid signature")),
+        };
+
+        let version = read_u32(input, big_endian).map_err(parse_error)?;
+        // Version 1 uses 64 bit offsets for files larger than 4 GB
+        let offset_size = match version {
+            0 => 4,
+            1 => 8,
+            _ => return Err(parse_error(format!("unsupported version {}", version))),
+        };
+        let sequence_count = read_u32(input, big_endian).map_err(parse_error)?;
+        let _reserved = read_u32(input, big_endian).map_err(parse_error)?;
+
+        let mut size = 16;
+        let mut entries = VecDeque::new();
+        for _ in 0..sequence_count {
+            let mut name_size = [0; 1];
+            input.read_exact(&mut name_size).map_err(parse_error)?;
+            let mut name = vec![0; name_size[0] as usize];
+            input.read_exact(&mut name).map_err(parse_error)?;
+            let name = String::from_utf8(name).map_err(parse_error)?;
+            let offset = match offset_size {
+                4 => read_u32(input, big_endian).map_err(parse_error)? as u64,
+                _ => read_u64(input, big_endian).map_err(parse_error)?,
+            };
+            size += 1 + name_size[0] as u64 + offset_size;
+            entries.push_back((name, offset));
+        }
+
+        return Ok(TwoBitHeader {
+            big_endian: big_endian,
+            entries: entries,
+            size: size,
+        });
+    }
+}
+
+struct TwoBitRecords {
+    input: Box<dyn Read + Send>,
+    big_endian: bool,
+    /// Sequences not read yet, ordered by their offset
+    entries: VecDeque<(String, u64)>,
+    /// Number of bytes consumed from the input
+    position: u64,
+    done: bool,
+}
+
+impl TwoBitRecords {
+    fn read_record(&mut self, name: String, offset: u64) -> Result<SequenceRecord, OrfFinderError> {
+        if offset < self.position {
+            return Err(parse_error(format!(
+                "sequence {} overlaps the previous sequence",
+                name
+            )));
+        }
+        io::copy(
+            &mut self.input.by_ref().take(offset - self.position),
+            &mut io::sink(),
+        )
+        .map_err(parse_error)?;
+        self.position = offset;
+
+        let dna_size = self.read_u32()? as usize;
+        let unknown_blocks = self.read_blocks()?;
+        // Soft masked blocks are skipped, the finder only handles uppercase bases
+        let _mask_blocks = self.read_blocks()?;
+        let _reserved = self.read_u32()?;
+
+        let mut packed = vec![0; dna_size.div_ceil(4)];
+        self.input.read_exact(&mut packed).map_err(parse_error)?;
+        self.position += packed.len() as u64;
+
+        let mut sequence: Vec<u8> = packed
+            .iter()
+            .flat_map(|byte| {
+                [6, 4, 2, 0].map(|shift| TWOBIT_BASES[(byte >> shift & 0b11) as usize])
+            })
+            .take(dna_size)
+            .collect();
+        for (start, size) in unknown_blocks {
+            let end = (start + size).min(dna_size);
+            sequence[start.min(end)..end].fill(b'N');
+        }
+
+        return Ok(SequenceRecord {
+            id: name,
+            // Only the bases above are written into the sequence
+            sequence: String::from_utf8(sequence).unwrap(),
+            packed: None,
+            offset: None,
+        });
+    }
+
+    fn read_u32(&mut self) -> Result<u32, OrfFinderError> {
+        let value = read_u32(&mut self.input, self.big_endian).map_err(parse_error)?;
+        self.position += 4;
+
+        return Ok(value);
+    }
+
+    /// Reads the count, starts and sizes of a block list
+    fn read_blocks(&mut self) -> Result<Vec<(usize, usize)>, OrfFinderError> {
+        let count = self.read_u32()? as usize;
+        let starts = (0..count)
+            .map(|_| self.read_u32())
+            .collect::<Result<Vec<_>, _>>()?;
+        let sizes = (0..count)
+            .map(|_| self.read_u32())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        return Ok(starts
+            .into_iter()
+            .zip(sizes)
+            .map(|(start, size)| (start as usize, size as usize))
+            .collect());
+    }
+}
+
+impl Iterator for TwoBitRecords {
+    type Item = Result<SequenceRecord, OrfFinderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let (name, offset) = self.entries.pop_front()?;
+
+        let record = self.read_record(name, offset);
+        self.done = record.is_err();
+
+        return Some(record);
+    }
+}
+
+fn read_u32(input: &mut impl Read, big_endian: bool) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    input.read_exact(&mut bytes)?;
+
+    return Ok(match big_endian {
+        true => u32::from_be_bytes(bytes),
+        false => u32::from_le_bytes(bytes),
+    });
+}
+
+fn read_u64(input: &mut impl Read, big_endian: bool) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    input.read_exact(&mut bytes)?;
+
+    return Ok(match big_endian {
+        true => u64::from_be_bytes(bytes),
+        false => u64::from_le_bytes(bytes),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        find_orfs_in_records, finder::finder_config::FinderConfig,
+        outwriter::outwriter::OutputType, test_utils::SharedBuffer,
+    };
+
+    use super::{is_twobit, read_twobit, TWOBIT_SIGNATURE};
+
+    /// Writes a little endian version 0 2bit file, unknown bases are given as `N`
+    fn write_twobit(records: &[(&str, &str)]) -> Vec<u8> {
+        let mut header = Vec::new();
+        for value in [TWOBIT_SIGNATURE, 0, records.len() as u32, 0] {
+            header.extend(value.to_le_bytes());
+        }
+        let index_size: usize = records.iter().map(|(name, _)| 1 + name.len() + 4).sum();
+
+        let mut data = Vec::new();
+        for (name, sequence) in records {
+            header.push(name.len() as u8);
+            header.extend(name.as_bytes());
+            header.extend(((16 + index_size + data.len()) as u32).to_le_bytes());
+
+            let unknown: Vec<u32> = (0..sequence.len() as u32)
+                .filter(|&index| sequence.as_bytes()[index as usize] == b'N')
+                .collect();
+            data.extend((sequence.len() as u32).to_le_bytes());
+            data.extend((unknown.len() as u32).to_le_bytes());
+            unknown
+                .iter()
+                .for_each(|start| data.extend(start.to_le_bytes()));
+            unknown.iter().for_each(|_| data.extend(1u32.to_le_bytes()));
+            data.extend([0; 8]);
+            for chunk in sequence.as_bytes().chunks(4) {
+                let mut byte = 0;
+                for (index, base) in chunk.iter().enumerate() {
+                    let code = b"TCAG".iter().position(|b| b == base).unwrap_or(0) as u8;
+                    byte |= code << (6 - 2 * index);
+                }
+                data.push(byte);
+            }
+        }
+
+        header.extend(data);
+        return header;
+    }
+
+    #[test]
+    fn reads_twobit_records() {
+        let twobit = write_twobit(&[("chr1", "ATGAAATAGNNC"), ("chr2", "GGCAT")]);
+        assert!(is_twobit(&twobit));
+
+        let records: Vec<_> = read_twobit(Box::new(std::io::Cursor::new(twobit)))
+            .map(|record| record.unwrap())
+            .collect();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].id, "chr1");
+        assert_eq!(records[0].sequence, "ATGAAATAGNNC");
+        assert_eq!(records[1].id, "chr2");
+        assert_eq!(records[1].sequence, "GGCAT");
+    }
+
+    #[test]
+    fn searches_records_with_unknown_blocks() {
+        let twobit = write_twobit(&[("chr1", "ATGAAATAGNNNNATGCCCTAA")]);
+        for packed in [false, true] {
+            let config = FinderConfig::builder()
+                .min_len(3)
+                .packed(packed)
+                .build()
+                .unwrap();
+            let records = read_twobit(Box::new(std::io::Cursor::new(twobit.clone())));
+            let out_target = SharedBuffer::default();
+            find_orfs_in_records(
+                records,
+                &config,
+                OutputType::TSV,
+                Box::new(out_target.clone()),
+                |_| {
+                    return Ok(vec![]);
+                },
+            )
+            .unwrap();
+
+            let tsv = out_target.contents();
+            let mut orfs: Vec<(&str, &str, &str)> = tsv
+                .lines()
+                .filter(|line| !line.starts_with('#') && !line.starts_with("seqid"))
+                .map(|line| {
+                    let columns: Vec<&str> = line.split('\t').collect();
+                    return (columns[1], columns[2], columns[3]);
+                })
+                .collect();
+            orfs.sort();
+            assert_eq!(orfs, vec![("0", "6", "+"), ("13", "19", "+")]);
+        }
+    }
+
+    #[test]
+    fn truncated_twobit_is_an_error() {
+        let mut twobit = write_twobit(&[("chr1", "ATGAAATAG")]);
+        twobit.truncate(twobit.len() - 1);
+
+        let mut records = read_twobit(Box::new(std::io::Cursor::new(twobit)));
+        assert!(records.next().unwrap().is_err());
+        assert!(records.next().is_none());
+    }
+}
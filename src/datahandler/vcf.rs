@@ -0,0 +1,195 @@
+use std::{
+    io::{BufRead, BufReader},
+    path::PathBuf,
+};
+
+use crate::error::error::OrfFinderError;
+
+use super::decompress::decompress;
+
+/// A sequence variant, an SNV, MNV or indel replacing the reference bases by the alternative ones.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Variant {
+    /// Identifier from the ID column, `<seqid>:<position>:<ref>><alt>` if the variant has none
+    pub id: String,
+    pub seqid: String,
+    /// Position of the first reference base, starting at 0
+    pub position: usize,
+    pub reference: String,
+    pub alternative: String,
+}
+
+impl Variant {
+    /// Position after the last reference base
+    pub fn end(&self) -> usize {
+        return self.position + self.reference.len();
+    }
+}
+
+/// The alleles of a VCF file applied to the reference.
+pub enum VcfSelection {
+    /// The first alternative allele of each variant
+    AllVariants,
+    /// The first alternative allele the sample carries on any haplotype
+    Sample(String),
+    /// The allele of the sample on the haplotype, starting at 0
+    Haplotype(String, usize),
+}
+
+/// Reads the variants of a VCF file, which may be compressed.
+/// Only variants of the selected sample or haplotype are returned, in the order of the file.
+/// Symbolic alleles, e.g. `<DEL>`, alleles with unknown bases (N) and filtered variants are skipped.
+pub fn parse_vcf_file(
+    path: PathBuf,
+    selection: &VcfSelection,
+) -> Result<Vec<Variant>, OrfFinderError> {
+    let vcf_error =
+        |message: String| OrfFinderError::VariantParse(format!("{}: {}", path.display(), message));
+
+    let file = std::fs::File::open(path.as_path())
+        .and_then(|file| decompress(Box::new(file)))
+        .map_err(|err| vcf_error(err.to_string()))?;
+    let mut sample_column = None;
+    let mut variants = Vec::new();
+
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|err| vcf_error(err.to_string()))?;
+        if line.starts_with("#CHROM") {
+            sample_column = match selection {
+                VcfSelection::AllVariants => None,
+                VcfSelection::Sample(sample) | VcfSelection::Haplotype(sample, _) => {
+                    let column = line.split('\t').position(|column| column == sample);
+                    Some(column.filter(|column| *column >= 9).ok_or_else(|| {
+                        vcf_error(format!("the sample {} is not part of the file", sample))
+                    })?)
+                }
+            };
+            continue;
+        }
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() < 8 {
+            return Err(vcf_error(format!(
+                "line {} has less than 8 columns",
+                index + 1
+            )));
+        }
+        if fields[6] != "PASS" && fields[6] != "." {
+            continue;
+        }
+
+        let alternatives: Vec<&str> = fields[4].split(',').collect();
+        let allele = match (selection, sample_column) {
+            (VcfSelection::AllVariants, _) => Some(1),
+            (_, None) => return Err(vcf_error("the file has no #CHROM header".to_string())),
+            (VcfSelection::Sample(_), Some(column)) => genotype(&fields, column)
+                .into_iter()
+                .flatten()
+                .find(|allele| *allele > 0),
+            (VcfSelection::Haplotype(_, haplotype), Some(column)) => {
+                genotype(&fields, column).get(*haplotype).copied().flatten()
+            }
+        };
+        let alternative = match allele.filter(|allele| *allele > 0) {
+            Some(allele) => alternatives.get(allele - 1).ok_or_else(|| {
+                vcf_error(format!(
+                    "the genotype in line {} names an unknown allele",
+                    index + 1
+                ))
+            })?,
+            None => continue,
+        };
+        let is_sequence = |allele: &str| {
+            return !allele.is_empty() && allele.bytes().all(|base| b"ACGTacgt".contains(&base));
+        };
+        if !is_sequence(fields[3]) || !is_sequence(alternative) {
+            continue;
+        }
+
+        let position: usize = fields[1]
+            .parse()
+            .ok()
+            .filter(|position| *position > 0)
+            .ok_or_else(|| vcf_error(format!("line {} has an invalid position", index + 1)))?;
+        let id = match fields[2] {
+            "." => format!("{}:{}:{}>{}", fields[0], position, fields[3], alternative),
+            id => id.to_string(),
+        };
+        variants.push(Variant {
+            id: id,
+            seqid: fields[0].to_string(),
+            position: position - 1,
+            reference: fields[3].to_ascii_uppercase(),
+            alternative: alternative.to_ascii_uppercase(),
+        });
+    }
+
+    return Ok(variants);
+}
+
+/// The alleles of the genotype of the sample, none for missing alleles.
+fn genotype(fields: &[&str], sample_column: usize) -> Vec<Option<usize>> {
+    let genotype = fields
+        .get(sample_column)
+        .and_then(|sample| sample.split(':').next())
+        .unwrap_or(".");
+
+    return genotype
+        .split(['|', '/'])
+        .map(|allele| allele.parse().ok())
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::TempFile;
+
+    use super::{parse_vcf_file, VcfSelection};
+
+    #[test]
+    fn selects_alleles_of_samples_and_haplotypes() {
+        let file = TempFile::new(
+            "variants.vcf",
+            "##fileformat=VCFv4.2\n\
+             #CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\ts1\ts2\n\
+             chr1\t10\trs1\tA\tG\t.\tPASS\t.\tGT\t0|1\t0|0\n\
+             chr1\t20\t.\tAC\tA,ACC\t.\tPASS\t.\tGT\t2|0\t1/1\n\
+             chr1\t30\trs3\tA\t<DEL>\t.\tPASS\t.\tGT\t1|1\t1|1\n\
+             chr1\t40\trs4\tT\tC\t.\tLowQual\t.\tGT\t1|1\t1|1\n\
+             chr1\t50\trs5\tA\tANN\t.\tPASS\t.\tGT\t1|1\t1|1\n",
+        );
+
+        let ids = |selection: VcfSelection| -> Vec<String> {
+            return parse_vcf_file(file.path().to_path_buf(), &selection)
+                .unwrap()
+                .into_iter()
+                .map(|variant| format!("{}={}", variant.id, variant.alternative))
+                .collect();
+        };
+
+        assert_eq!(
+            ids(VcfSelection::AllVariants),
+            vec!["rs1=G", "chr1:20:AC>A=A"]
+        );
+        assert_eq!(
+            ids(VcfSelection::Sample("s1".to_string())),
+            vec!["rs1=G", "chr1:20:AC>ACC=ACC"]
+        );
+        assert_eq!(
+            ids(VcfSelection::Haplotype("s1".to_string(), 0)),
+            vec!["chr1:20:AC>ACC=ACC"]
+        );
+        assert_eq!(
+            ids(VcfSelection::Sample("s2".to_string())),
+            vec!["chr1:20:AC>A=A"]
+        );
+        assert!(parse_vcf_file(
+            file.path().to_path_buf(),
+            &VcfSelection::Sample("s3".to_string())
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,151 @@
+use std::{any::Any, fmt, io};
+
+use crossbeam::channel::SendError;
+
+/// Errors reported by the ORF finder, from reading the input to writing the results.
+#[derive(Debug)]
+pub enum OrfFinderError {
+    /// The sequence input could not be read or parsed
+    InputParse(String),
+    /// The sequence contains a base other than A, C, G or T, the position is 0-based
+    InvalidBase { base: u8, position: usize },
+    /// No translational table with this number is available
+    UnknownTable(u8),
+    /// The file of masked areas could not be read or parsed
+    MaskParse(String),
+    /// The file of annotated coding sequences could not be read or parsed
+    AnnotationParse(String),
+    /// The variants could not be read, parsed or applied to the reference
+    VariantParse(String),
+    /// A coding or gene model could not be read, parsed or written
+    Model(String),
+    /// The selected options can not be combined
+    InvalidConfig(String),
+    /// The results could not be written
+    OutputIo(io::Error),
+    /// The next stage of the pipeline stopped receiving ORFs, caused by an error in that stage
+    Disconnected,
+    /// A thread of the pipeline panicked
+    Thread(String),
+}
+
+impl OrfFinderError {
+    /// Creates the error of a panicked thread from its panic payload.
+    pub fn from_panic(panic: Box<dyn Any + Send>) -> Self {
+        let message = match (panic.downcast_ref::<&str>(), panic.downcast_ref::<String>()) {
+            (Some(message), _) => message.to_string(),
+            (None, Some(message)) => message.clone(),
+            (None, None) => "unknown panic".to_string(),
+        };
+
+        return OrfFinderError::Thread(message);
+    }
+}
+
+impl fmt::Display for OrfFinderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            OrfFinderError::InputParse(message) => {
+                write!(f, "could not read the sequence input: {}", message)
+            }
+            OrfFinderError::InvalidBase { base, position } => write!(
+                f,
+                "invalid base {:?} at position {} of the sequence, only A, C, G, T, U and N are supported",
+                *base as char,
+                position + 1
+            ),
+            OrfFinderError::UnknownTable(table) => {
+                write!(f, "translational table {} is not supported", table)
+            }
+            OrfFinderError::MaskParse(message) => {
+                write!(f, "could not read the masked areas: {}", message)
+            }
+            OrfFinderError::AnnotationParse(message) => {
+                write!(f, "could not read the CDS annotation: {}", message)
+            }
+            OrfFinderError::VariantParse(message) => {
+                write!(f, "could not apply the variants: {}", message)
+            }
+            OrfFinderError::Model(message) => write!(f, "{}", message),
+            OrfFinderError::InvalidConfig(message) => write!(f, "invalid configuration: {}", message),
+            OrfFinderError::OutputIo(err) => write!(f, "could not write the results: {}", err),
+            OrfFinderError::Disconnected => {
+                write!(f, "a stage of the pipeline stopped before all ORFs were processed")
+            }
+            OrfFinderError::Thread(message) => {
+                write!(f, "a thread of the pipeline panicked: {}", message)
+            }
+        };
+    }
+}
+
+impl std::error::Error for OrfFinderError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        return match self {
+            OrfFinderError::OutputIo(err) => Some(err),
+            _ => None,
+        };
+    }
+}
+
+impl<T> From<SendError<T>> for OrfFinderError {
+    fn from(_: SendError<T>) -> Self {
+        return OrfFinderError::Disconnected;
+    }
+}
+
+/// Returns the error that caused a pipeline to fail.
+/// Stages before a failed stage are disconnected, so any other error takes precedence over `Disconnected`.
+pub fn root_cause(
+    results: impl IntoIterator<Item = Result<(), OrfFinderError>>,
+) -> Result<(), OrfFinderError> {
+    let mut disconnected = false;
+    for result in results {
+        match result {
+            Ok(()) => {}
+            Err(OrfFinderError::Disconnected) => disconnected = true,
+            Err(err) => return Err(err),
+        }
+    }
+
+    return match disconnected {
+        true => Err(OrfFinderError::Disconnected),
+        false => Ok(()),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{root_cause, OrfFinderError};
+
+    #[test]
+    fn root_cause_prefers_stage_errors() {
+        let results = vec![
+            Err(OrfFinderError::Disconnected),
+            Ok(()),
+            Err(OrfFinderError::UnknownTable(2)),
+        ];
+        assert!(matches!(
+            root_cause(results),
+            Err(OrfFinderError::UnknownTable(2))
+        ));
+
+        assert!(matches!(
+            root_cause(vec![Ok(()), Err(OrfFinderError::Disconnected)]),
+            Err(OrfFinderError::Disconnected)
+        ));
+        assert!(root_cause(vec![Ok(()), Ok(())]).is_ok());
+    }
+
+    #[test]
+    fn invalid_base_message_is_one_based() {
+        let err = OrfFinderError::InvalidBase {
+            base: b'R',
+            position: 9,
+        };
+        assert_eq!(
+            err.to_string(),
+            "invalid base 'R' at position 10 of the sequence, only A, C, G, T, U and N are supported"
+        );
+    }
+}
@@ -1 +1,99 @@
-trait Finder {}
+use crossbeam::channel::{bounded, unbounded, Sender};
+use crossbeam::thread;
+
+use crate::{
+    error::error::{root_cause, OrfFinderError},
+    models::models::ORF,
+    outwriter::outwriter::OutWriter,
+    postprocessor::postprocessor::PostProcessor,
+};
+
+use super::{
+    orf_search::OrfSearch, sequential_finder::SequentialFinder, threaded_finder::ThreadedFinder,
+};
+
+/// Minimum number of threads to run the writer, a finder and a transcriber in parallel
+pub const MIN_PARALLEL_THREADS: usize = 3;
+
+/// A backend searching the ORFs of a single sequence.
+/// The sequence is handed to the backend on creation, `find` sends all ORFs found on it in an unspecified order.
+pub trait Finder {
+    /// Searches all ORFs and sends each of them into `orf_sender`, returns once all ORFs have been sent.
+    /// Fails with `Disconnected` if the receiver is dropped before.
+    fn find(&self, orf_sender: Sender<ORF>) -> Result<(), OrfFinderError>;
+}
+
+/// Searches all ORFs, passes them through the post processors and writes them with the `OutWriter`.
+/// The post processors and the writer each run on their own thread, the finder runs on the calling thread.
+/// A failing stage stops the pipeline and its error is returned.
+pub fn run_pipeline(
+    finder: &(dyn Finder + Sync),
+    postprocessors: &[Box<dyn PostProcessor + Send + Sync>],
+    outwriter: &(dyn OutWriter + Send + Sync),
+) -> Result<(), OrfFinderError> {
+    let (orf_sender, orf_receiver) = bounded(100);
+
+    let results = thread::scope(|s| {
+        // Chains the post processors between the finder and the writer
+        let mut stage_handles = Vec::new();
+        let mut stage_receiver = orf_receiver;
+        for postprocessor in postprocessors.iter() {
+            let (stage_sender, next_stage_receiver) = bounded(100);
+            let cloned_stage_receiver = stage_receiver;
+            stage_handles
+                .push(s.spawn(move |_| postprocessor.process(cloned_stage_receiver, stage_sender)));
+            stage_receiver = next_stage_receiver;
+        }
+
+        stage_handles.push(s.spawn(move |_| outwriter.write(stage_receiver)));
+
+        let mut results = vec![finder.find(orf_sender)];
+        for stage_handle in stage_handles {
+            let result = stage_handle
+                .join()
+                .unwrap_or_else(|panic| Err(OrfFinderError::from_panic(panic)));
+            results.push(result);
+        }
+
+        results
+    })
+    .map_err(OrfFinderError::from_panic)?;
+
+    return root_cause(results);
+}
+
+/// Runs the finder, the post processors and the writer one after another on the calling thread,
+/// buffering the results of each stage.
+pub fn run_sequential_pipeline(
+    finder: &dyn Finder,
+    postprocessors: &[Box<dyn PostProcessor + Send + Sync>],
+    outwriter: &dyn OutWriter,
+) -> Result<(), OrfFinderError> {
+    let (orf_sender, mut stage_receiver) = unbounded();
+    finder.find(orf_sender)?;
+
+    for postprocessor in postprocessors.iter() {
+        let (stage_sender, next_stage_receiver) = unbounded();
+        postprocessor.process(stage_receiver, stage_sender)?;
+        stage_receiver = next_stage_receiver;
+    }
+
+    return outwriter.write(stage_receiver);
+}
+
+/// Runs the search through the pipeline with the backend fitting the number of threads.
+/// Below `MIN_PARALLEL_THREADS` the sequential finder is used, otherwise one thread is reserved for the writer.
+pub fn run_search(
+    search: OrfSearch,
+    threads: usize,
+    postprocessors: &[Box<dyn PostProcessor + Send + Sync>],
+    outwriter: &(dyn OutWriter + Send + Sync),
+) -> Result<(), OrfFinderError> {
+    if threads < MIN_PARALLEL_THREADS {
+        let finder = SequentialFinder::new(search);
+        return run_sequential_pipeline(&finder, postprocessors, outwriter);
+    }
+
+    let finder = ThreadedFinder::new(search, threads - 1);
+    return run_pipeline(&finder, postprocessors, outwriter);
+}
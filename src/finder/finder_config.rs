@@ -0,0 +1,295 @@
+use std::{path::PathBuf, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    datahandler::{
+        mask_file::{merge_areas, parse_mask_gff3_file, MaskedAreas},
+        trans_table::parse_translational_table,
+    },
+    error::error::OrfFinderError,
+    models::models::KozakStrength,
+};
+
+use super::{start_selection::StartSelection, strand_selection::StrandSelection};
+
+/// Settings of an ORF search, serialisable so that runs can be configured from a JSON file.
+/// Missing fields of a configuration file take their default values.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct FinderConfig {
+    /// Number of the translational table to use
+    pub translational_table: u8,
+    /// ORFs of at most this many bases are dropped
+    pub min_len: usize,
+    /// ORFs of more than this many bases are dropped, unlimited if none
+    pub max_len: Option<usize>,
+    /// Indicates if the sequence is circular, so that ORFs may span its end
+    pub circular: bool,
+    /// GFF3 file of the masked areas, only its path is recorded in the configuration
+    pub mask_file: Option<PathBuf>,
+    /// Masked areas of each sequence, ORFs overlapping them are dropped
+    #[serde(skip)]
+    pub masked_areas: Arc<MaskedAreas>,
+    /// Which start codons to report for ORFs sharing a stop codon
+    pub start_selection: StartSelection,
+    /// The strands to search
+    pub strand: StrandSelection,
+    /// The frames 1 to 3 to search on each strand, frame 1 starts at the first base of the strand
+    pub frames: Vec<u8>,
+    /// Number of bases upstream of each start codon searched for a ribosome binding site, disabled if none
+    pub rbs_window: Option<usize>,
+    /// Indicates if the Kozak context of each start codon is scored
+    pub kozak_scoring: bool,
+    /// Minimum Kozak strength of a start codon to be reported, implies Kozak scoring
+    pub min_kozak_strength: Option<KozakStrength>,
+    /// Indicates if the ORFs of RNA input are reported with U, otherwise all sequences are reported as DNA
+    pub preserve_alphabet: bool,
+    /// Indicates if the sequence is stored with two bits per base, which takes a fraction of the memory but is slower
+    pub packed: bool,
+    /// Number of threads to use
+    pub threads: usize,
+}
+
+impl Default for FinderConfig {
+    fn default() -> Self {
+        let threads = std::thread::available_parallelism()
+            .map(|threads| threads.get())
+            .unwrap_or(1);
+
+        return FinderConfig {
+            translational_table: 11,
+            min_len: 30,
+            max_len: None,
+            circular: false,
+            mask_file: None,
+            masked_areas: Arc::new(MaskedAreas::new()),
+            start_selection: StartSelection::All,
+            strand: StrandSelection::Both,
+            frames: vec![1, 2, 3],
+            rbs_window: None,
+            kozak_scoring: false,
+            min_kozak_strength: None,
+            preserve_alphabet: false,
+            packed: false,
+            threads: threads,
+        };
+    }
+}
+
+impl FinderConfig {
+    pub fn builder() -> FinderConfigBuilder {
+        return FinderConfigBuilder {
+            config: FinderConfig::default(),
+        };
+    }
+
+    /// Reads and validates a configuration in JSON format.
+    pub fn from_file(path: PathBuf) -> Result<FinderConfig, OrfFinderError> {
+        let config_error = |err: &dyn std::fmt::Display| {
+            OrfFinderError::InvalidConfig(format!("{}: {}", path.display(), err))
+        };
+
+        let config_file = std::fs::File::open(path.as_path()).map_err(|err| config_error(&err))?;
+        let mut config: FinderConfig =
+            serde_json::from_reader(config_file).map_err(|err| config_error(&err))?;
+        config.read_mask_file()?;
+        config.validate()?;
+
+        return Ok(config);
+    }
+
+    /// Reads the masked areas from the mask file, if any.
+    pub fn read_mask_file(&mut self) -> Result<(), OrfFinderError> {
+        if let Some(mask_file) = &self.mask_file {
+            self.masked_areas = Arc::new(parse_mask_gff3_file(mask_file.clone())?);
+        }
+
+        return Ok(());
+    }
+
+    /// The configuration as a single line of JSON, e.g. to record it in output headers.
+    pub fn to_json(&self) -> String {
+        return serde_json::to_string(self).unwrap_or_default();
+    }
+
+    /// Checks that the translational table is known and all values are within range.
+    pub fn validate(&self) -> Result<(), OrfFinderError> {
+        parse_translational_table(self.translational_table)?;
+
+        if self.threads == 0 {
+            return Err(OrfFinderError::InvalidConfig(
+                "at least 1 thread is required".to_string(),
+            ));
+        }
+        if self.frames.is_empty() || self.frames.iter().any(|frame| !(1..=3).contains(frame)) {
+            return Err(OrfFinderError::InvalidConfig(format!(
+                "the frames {:?} have to be between 1 and 3",
+                self.frames
+            )));
+        }
+        if let Some(max_len) = self.max_len {
+            if max_len <= self.min_len {
+                return Err(OrfFinderError::InvalidConfig(format!(
+                    "the maximum ORF length {} has to exceed the minimum ORF length {}",
+                    max_len, self.min_len
+                )));
+            }
+        }
+
+        return Ok(());
+    }
+}
+
+/// Builds a `FinderConfig` starting from the defaults, `build` validates the result.
+pub struct FinderConfigBuilder {
+    config: FinderConfig,
+}
+
+impl FinderConfigBuilder {
+    pub fn translational_table(mut self, translational_table: u8) -> Self {
+        self.config.translational_table = translational_table;
+        return self;
+    }
+
+    pub fn min_len(mut self, min_len: usize) -> Self {
+        self.config.min_len = min_len;
+        return self;
+    }
+
+    pub fn max_len(mut self, max_len: Option<usize>) -> Self {
+        self.config.max_len = max_len;
+        return self;
+    }
+
+    pub fn circular(mut self, circular: bool) -> Self {
+        self.config.circular = circular;
+        return self;
+    }
+
+    /// The masked areas are read from the file by `build`
+    pub fn mask_file(mut self, mask_file: Option<PathBuf>) -> Self {
+        self.config.mask_file = mask_file;
+        return self;
+    }
+
+    pub fn masked_areas(mut self, mut masked_areas: MaskedAreas) -> Self {
+        for areas in masked_areas.values_mut() {
+            merge_areas(areas);
+        }
+        self.config.masked_areas = Arc::new(masked_areas);
+        return self;
+    }
+
+    pub fn start_selection(mut self, start_selection: StartSelection) -> Self {
+        self.config.start_selection = start_selection;
+        return self;
+    }
+
+    pub fn strand(mut self, strand: StrandSelection) -> Self {
+        self.config.strand = strand;
+        return self;
+    }
+
+    pub fn frames(mut self, frames: Vec<u8>) -> Self {
+        self.config.frames = frames;
+        return self;
+    }
+
+    pub fn rbs_window(mut self, rbs_window: Option<usize>) -> Self {
+        self.config.rbs_window = rbs_window;
+        return self;
+    }
+
+    pub fn kozak_scoring(mut self, kozak_scoring: bool) -> Self {
+        self.config.kozak_scoring = kozak_scoring;
+        return self;
+    }
+
+    pub fn min_kozak_strength(mut self, min_kozak_strength: Option<KozakStrength>) -> Self {
+        self.config.min_kozak_strength = min_kozak_strength;
+        return self;
+    }
+
+    pub fn preserve_alphabet(mut self, preserve_alphabet: bool) -> Self {
+        self.config.preserve_alphabet = preserve_alphabet;
+        return self;
+    }
+
+    pub fn packed(mut self, packed: bool) -> Self {
+        self.config.packed = packed;
+        return self;
+    }
+
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.config.threads = threads;
+        return self;
+    }
+
+    pub fn build(mut self) -> Result<FinderConfig, OrfFinderError> {
+        self.config.read_mask_file()?;
+        self.config.validate()?;
+        return Ok(self.config);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{error::error::OrfFinderError, finder::start_selection::StartSelection};
+
+    use super::FinderConfig;
+
+    #[test]
+    fn builder_validates_config() {
+        let config = FinderConfig::builder()
+            .min_len(90)
+            .max_len(Some(3000))
+            .start_selection(StartSelection::Longest)
+            .threads(2)
+            .build()
+            .unwrap();
+        assert_eq!(config.min_len, 90);
+        assert_eq!(config.max_len, Some(3000));
+
+        let unknown_table = FinderConfig::builder().translational_table(2).build();
+        assert!(matches!(
+            unknown_table,
+            Err(OrfFinderError::UnknownTable(2))
+        ));
+        let no_threads = FinderConfig::builder().threads(0).build();
+        assert!(matches!(no_threads, Err(OrfFinderError::InvalidConfig(_))));
+        let empty_range = FinderConfig::builder()
+            .min_len(90)
+            .max_len(Some(90))
+            .build();
+        assert!(matches!(empty_range, Err(OrfFinderError::InvalidConfig(_))));
+        let no_frames = FinderConfig::builder().frames(vec![]).build();
+        assert!(matches!(no_frames, Err(OrfFinderError::InvalidConfig(_))));
+        let unknown_frame = FinderConfig::builder().frames(vec![1, 4]).build();
+        assert!(matches!(
+            unknown_frame,
+            Err(OrfFinderError::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn config_round_trips_through_json() {
+        let config = FinderConfig::builder()
+            .circular(true)
+            .rbs_window(Some(20))
+            .threads(3)
+            .build()
+            .unwrap();
+
+        let parsed: FinderConfig = serde_json::from_str(&config.to_json()).unwrap();
+        assert_eq!(parsed, config);
+    }
+
+    #[test]
+    fn missing_fields_take_defaults() {
+        let config: FinderConfig = serde_json::from_str(r#"{"min_len": 120}"#).unwrap();
+        assert_eq!(config.min_len, 120);
+        assert_eq!(config.translational_table, 11);
+        assert_eq!(config.start_selection, StartSelection::All);
+    }
+}
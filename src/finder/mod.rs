@@ -1,2 +1,11 @@
 pub mod finder;
+pub mod finder_config;
+pub mod orf_iter;
+pub mod orf_search;
+pub mod sequential_finder;
+pub mod start_selection;
+pub mod strand_selection;
+pub mod strands;
+#[cfg(test)]
+pub(crate) mod test_utils;
 pub mod threaded_finder;
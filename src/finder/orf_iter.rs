@@ -0,0 +1,148 @@
+use std::thread::{self, JoinHandle};
+
+use crossbeam::channel::{bounded, never, Receiver};
+
+use crate::{
+    error::error::OrfFinderError, models::models::ORF, outwriter::channel_writer::ChannelWriter,
+    postprocessor::postprocessor::PostProcessor,
+};
+
+use super::{finder::run_search, orf_search::OrfSearch};
+
+/// Iterates over the ORFs of a search running on a background thread.
+/// ORFs are yielded as soon as they passed all post processors, a failed search ends the iteration with an error.
+/// Dropping the iterator early stops the search and waits for its thread.
+pub struct OrfIter {
+    orfs: Receiver<ORF>,
+    search_handle: Option<JoinHandle<Result<(), OrfFinderError>>>,
+}
+
+impl OrfIter {
+    pub fn new(
+        search: OrfSearch,
+        threads: usize,
+        postprocessors: Vec<Box<dyn PostProcessor + Send + Sync>>,
+    ) -> Self {
+        let (orf_sender, orf_receiver) = bounded(100);
+
+        let search_handle = thread::spawn(move || {
+            let writer = ChannelWriter::detached(orf_sender);
+            return run_search(search, threads, &postprocessors, &writer);
+        });
+
+        return OrfIter {
+            orfs: orf_receiver,
+            search_handle: Some(search_handle),
+        };
+    }
+}
+
+impl Iterator for OrfIter {
+    type Item = Result<ORF, OrfFinderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Ok(orf) = self.orfs.recv() {
+            return Some(Ok(orf));
+        }
+
+        // All senders are gone, the search either finished or failed
+        let search_handle = self.search_handle.take()?;
+        return match search_handle.join() {
+            Ok(Ok(())) => None,
+            Ok(Err(err)) => Some(Err(err)),
+            Err(panic) => Some(Err(OrfFinderError::from_panic(panic))),
+        };
+    }
+}
+
+impl Drop for OrfIter {
+    fn drop(&mut self) {
+        // Disconnects the search from its consumer, so the next sent ORF fails and ends the search
+        drop(std::mem::replace(&mut self.orfs, never()));
+        if let Some(search_handle) = self.search_handle.take() {
+            let _ = search_handle.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crossbeam::channel::{Receiver, Sender};
+
+    use crate::{
+        error::error::OrfFinderError,
+        finder::{
+            sequential_finder::SequentialFinder,
+            test_utils::{find_orf_coordinates, search},
+        },
+        models::models::{Direction, ORF},
+        postprocessor::postprocessor::PostProcessor,
+    };
+
+    use super::OrfIter;
+
+    /// Fails after passing on the first ORF
+    struct FailingPostProcessor {}
+
+    impl PostProcessor for FailingPostProcessor {
+        fn process(
+            &self,
+            orfs: Receiver<ORF>,
+            orf_sender: Sender<ORF>,
+        ) -> Result<(), OrfFinderError> {
+            orf_sender.send(orfs.recv().unwrap())?;
+            return Err(OrfFinderError::InvalidConfig("failing stage".to_string()));
+        }
+    }
+
+    #[test]
+    fn iterator_yields_all_orfs() {
+        let sequence = fs::read_to_string("resources/sequences/NC_011604.1_normal.fasta").unwrap();
+
+        let expected = find_orf_coordinates(&SequentialFinder::new(search(&sequence, false, 30)));
+        for threads in [1, 4] {
+            let mut coordinates: Vec<_> =
+                OrfIter::new(search(&sequence, false, 30), threads, vec![])
+                    .map(|orf| {
+                        let orf = orf.unwrap();
+                        (
+                            orf.start_position,
+                            orf.stop_position,
+                            orf.direction == Direction::REVERSE,
+                        )
+                    })
+                    .collect();
+            coordinates.sort();
+
+            assert_eq!(coordinates, expected);
+        }
+    }
+
+    #[test]
+    fn iterator_can_be_dropped_early() {
+        let sequence = fs::read_to_string("resources/sequences/NC_011604.1_normal.fasta").unwrap();
+
+        let first = OrfIter::new(search(&sequence, false, 30), 4, vec![]).next();
+        assert!(first.unwrap().is_ok());
+    }
+
+    #[test]
+    fn failing_stage_ends_iteration_with_its_error() {
+        let sequence = fs::read_to_string("resources/sequences/NC_011604.1_normal.fasta").unwrap();
+
+        for threads in [1, 4] {
+            let results: Vec<_> = OrfIter::new(
+                search(&sequence, false, 30),
+                threads,
+                vec![Box::new(FailingPostProcessor {})],
+            )
+            .collect();
+
+            let (last, orfs) = results.split_last().unwrap();
+            assert!(orfs.len() <= 1 && orfs.iter().all(|orf| orf.is_ok()));
+            assert!(matches!(last, Err(OrfFinderError::InvalidConfig(_))));
+        }
+    }
+}
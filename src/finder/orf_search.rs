@@ -0,0 +1,541 @@
+use std::sync::Arc;
+
+use crate::{
+    datahandler::{
+        self,
+        filehandler::RecordOffset,
+        mask_file::{overlaps_masked_area, MaskedAreas},
+        trans_table::{CodonLookup, TranslationalTable},
+    },
+    error::error::OrfFinderError,
+    models::{
+        models::{Alphabet, Direction, KozakStrength, ORFPositions, SequenceSlice, ORF},
+        packed_sequence::PackedSequence,
+    },
+    scoring::{
+        kozak::{kozak_context, KOZAK_UPSTREAM_LEN},
+        rbs::{RbsScanner, DEFAULT_RBS_WINDOW},
+    },
+};
+
+use super::{
+    finder_config::FinderConfig,
+    start_selection::{select_starts, StartSelection},
+    strand_selection::StrandSelection,
+    strands::Strands,
+};
+
+const U8_A: u8 = 'A' as u8;
+const U8_C: u8 = 'C' as u8;
+const U8_T: u8 = 'T' as u8;
+const U8_G: u8 = 'G' as u8;
+const U8_N: u8 = 'N' as u8;
+
+/// Identifier of sequences without a name, e.g. raw sequences
+pub const DEFAULT_SEQID: &str = "sequence";
+
+/// Both strands of a sequence together with the settings shared by all finder backends.
+pub struct OrfSearch {
+    /// Identifier of the sequence record, attached to each ORF
+    pub seqid: Arc<str>,
+    pub strands: Strands,
+    /// Areas on the forward strand of each source sequence, ORFs overlapping the areas of their sequence are dropped
+    pub masked_areas: Arc<MaskedAreas>,
+    pub translational_table: TranslationalTable,
+    pub codon_lookup: CodonLookup,
+    pub min_len: usize,
+    pub max_len: Option<usize>,
+    pub circular: bool,
+    pub rbs_scanner: Option<RbsScanner>,
+    pub start_selection: StartSelection,
+    /// The strands to search
+    pub strand_selection: StrandSelection,
+    /// Offsets of the frames to search on each strand
+    pub frame_offsets: Vec<usize>,
+    pub kozak_scoring: bool,
+    pub min_kozak_strength: Option<KozakStrength>,
+    /// Alphabet in which the sequences of the ORFs are reported
+    pub alphabet: Alphabet,
+    /// Number of bases of the sequence, without the bases appended to circular sequences
+    pub sequence_len: usize,
+    /// Location of the sequence within a longer sequence, ORF positions are reported on the longer sequence
+    pub offset: Option<RecordOffset>,
+}
+
+impl OrfSearch {
+    /// Prepares both strands of the sequence for a search with the given settings.
+    /// RNA is searched as DNA, its ORFs are only reported with U if the config preserves the alphabet.
+    /// Fails if the translational table is unknown or the sequence contains invalid bases.
+    pub fn new(sequence: String, config: &FinderConfig) -> Result<Self, OrfFinderError> {
+        let mut fw_seq_bytes = sequence.into_bytes();
+        let input_alphabet = Alphabet::normalize(&mut fw_seq_bytes);
+        let sequence_len = fw_seq_bytes.len();
+        let strands = match config.packed {
+            true => Strands::Packed {
+                sequence: PackedSequence::from_bases(&fw_seq_bytes)?,
+                circular: config.circular,
+            },
+            false => OrfSearch::plain_strands(fw_seq_bytes, config.circular)?,
+        };
+
+        return OrfSearch::with_strands(strands, sequence_len, input_alphabet, config);
+    }
+
+    /// Prepares a search of an already packed sequence, e.g. packed while reading it, regardless of `config.packed`.
+    /// Fails if the translational table is unknown.
+    pub fn from_packed(
+        sequence: PackedSequence,
+        config: &FinderConfig,
+    ) -> Result<Self, OrfFinderError> {
+        let sequence_len = sequence.len();
+        let strands = Strands::Packed {
+            sequence: sequence,
+            circular: config.circular,
+        };
+
+        return OrfSearch::with_strands(strands, sequence_len, Alphabet::Dna, config);
+    }
+
+    fn with_strands(
+        strands: Strands,
+        sequence_len: usize,
+        input_alphabet: Alphabet,
+        config: &FinderConfig,
+    ) -> Result<Self, OrfFinderError> {
+        let table =
+            datahandler::trans_table::parse_translational_table(config.translational_table)?;
+
+        // Selecting starts by their RBS requires the RBS detection even if no window was given
+        let rbs_scanner = match (config.rbs_window, config.start_selection) {
+            (Some(window), _) => Some(RbsScanner::new(window)),
+            (None, StartSelection::Rbs) => Some(RbsScanner::new(DEFAULT_RBS_WINDOW)),
+            (None, _) => None,
+        };
+        let kozak_scoring = config.kozak_scoring
+            || config.min_kozak_strength.is_some()
+            || config.start_selection == StartSelection::Kozak;
+
+        let search = OrfSearch {
+            seqid: Arc::from(DEFAULT_SEQID),
+            strands: strands,
+            codon_lookup: CodonLookup::new(&table),
+            translational_table: table,
+            masked_areas: Arc::clone(&config.masked_areas),
+            min_len: config.min_len,
+            max_len: config.max_len,
+            circular: config.circular,
+            rbs_scanner: rbs_scanner,
+            start_selection: config.start_selection,
+            strand_selection: config.strand,
+            frame_offsets: OrfSearch::frame_offsets(&config.frames),
+            kozak_scoring: kozak_scoring,
+            min_kozak_strength: config.min_kozak_strength,
+            alphabet: match config.preserve_alphabet {
+                true => input_alphabet,
+                false => Alphabet::Dna,
+            },
+            sequence_len: sequence_len,
+            offset: None,
+        };
+
+        return Ok(search);
+    }
+
+    /// Sorted offsets of the frames without duplicates, frame 1 has offset 0.
+    fn frame_offsets(frames: &[u8]) -> Vec<usize> {
+        let mut offsets: Vec<usize> = frames.iter().map(|frame| *frame as usize - 1).collect();
+        offsets.sort();
+        offsets.dedup();
+
+        return offsets;
+    }
+
+    /// Names the searched sequence, e.g. after its FASTA record.
+    pub fn with_seqid(mut self, seqid: &str) -> Self {
+        self.seqid = Arc::from(seqid);
+        return self;
+    }
+
+    /// Places the sequence within a longer sequence, e.g. a region of a chromosome.
+    pub fn with_offset(mut self, offset: RecordOffset) -> Self {
+        self.offset = Some(offset);
+        return self;
+    }
+
+    fn plain_strands(mut fw_seq_bytes: Vec<u8>, circular: bool) -> Result<Strands, OrfFinderError> {
+        let mut rev_complement_seq_bytes = OrfSearch::reverse_complement(&fw_seq_bytes)?;
+
+        if circular {
+            fw_seq_bytes = OrfSearch::append_two_bases(fw_seq_bytes);
+            rev_complement_seq_bytes = OrfSearch::append_two_bases(rev_complement_seq_bytes);
+        }
+
+        return Ok(Strands::Plain {
+            forward: Arc::from(fw_seq_bytes),
+            reverse: Arc::from(rev_complement_seq_bytes),
+        });
+    }
+
+    /// Appends the first two bases, so that codons spanning the end of a circular sequence are read.
+    #[inline(always)]
+    fn append_two_bases(mut sequence: Vec<u8>) -> Vec<u8> {
+        let wrapped_len = sequence.len().min(2);
+        sequence.extend_from_within(..wrapped_len);
+
+        return sequence;
+    }
+
+    /// The reverse complement of a DNA sequence, fails on the first base that is not A, C, G, T or N.
+    #[inline(always)]
+    fn reverse_complement(sequence: &[u8]) -> Result<Vec<u8>, OrfFinderError> {
+        let mut complement_seq = Vec::with_capacity(sequence.len());
+        for (position, base) in sequence.iter().enumerate() {
+            let complement_base = OrfSearch::complement(*base, position)?;
+            complement_seq.push(complement_base);
+        }
+        complement_seq.reverse();
+
+        return Ok(complement_seq);
+    }
+
+    #[inline(always)]
+    fn complement(base: u8, position: usize) -> Result<u8, OrfFinderError> {
+        let complement_base = match base {
+            U8_A => U8_T,
+            U8_T => U8_A,
+            U8_C => U8_G,
+            U8_G => U8_C,
+            // Unknown bases stay unknown, codons containing them are sense codons
+            U8_N => U8_N,
+            _ => return Err(OrfFinderError::InvalidBase { base, position }),
+        };
+
+        return Ok(complement_base);
+    }
+
+    /// Indicates if the ORF, including its stop codon, overlaps one of the masked areas.
+    /// * `areas` - The masked areas of the sequence
+    /// * `direction` - The strand the positions are located on
+    /// * `start_position` - Position of the start codon on the source sequence
+    /// * `stop_position` - Position of the stop codon on the source sequence
+    fn is_masked(
+        &self,
+        areas: &[(u64, u64)],
+        direction: Direction,
+        start_position: usize,
+        stop_position: usize,
+    ) -> bool {
+        let source_len = self
+            .offset
+            .map_or(self.sequence_len, |offset| offset.source_len);
+        let overlaps = |start: usize, end: usize| {
+            let (start, end) = match direction {
+                Direction::FORWARD => (start, end),
+                Direction::REVERSE => (source_len - end, source_len - start),
+            };
+            return overlaps_masked_area(areas, start as u64, end as u64);
+        };
+
+        let stop_end = source_len.min(stop_position + 3);
+        // ORFs wrapping around the end of circular sequences cover two areas
+        return match start_position <= stop_position {
+            true => overlaps(start_position, stop_end),
+            false => overlaps(start_position, source_len) || overlaps(0, stop_end),
+        };
+    }
+
+    /// Creates the ORFs of all starts sharing a stop codon that pass the length, mask and start filters.
+    pub fn transcribe(&self, orf_positions: ORFPositions) -> Vec<ORF> {
+        let direction = orf_positions.strand;
+        let stop_pos = orf_positions.stop_position;
+        let sequence_len = self.strands.len(direction);
+        let position_offset = self.offset.map_or(0, |offset| {
+            offset.strand_offset(direction, self.sequence_len)
+        });
+        let masked_areas = self
+            .masked_areas
+            .get(self.seqid.as_ref())
+            .map_or(&[][..], |areas| areas.as_slice());
+
+        let mut candidates = Vec::with_capacity(orf_positions.start_positions.len());
+        for start_pos in orf_positions.start_positions {
+            // ORFs of circular sequences may wrap around the end, past the two appended bases
+            let orf_len = match start_pos <= stop_pos {
+                true => stop_pos - start_pos,
+                false => sequence_len - 2 - start_pos + stop_pos,
+            };
+            if orf_len <= self.min_len || self.max_len.is_some_and(|max_len| orf_len > max_len) {
+                continue;
+            }
+            if !masked_areas.is_empty()
+                && self.is_masked(
+                    masked_areas,
+                    direction,
+                    start_pos + position_offset,
+                    stop_pos + position_offset,
+                )
+            {
+                continue;
+            }
+
+            let rbs = self.rbs_scanner.as_ref().and_then(|scanner| {
+                let window_start = start_pos.saturating_sub(scanner.window());
+                let upstream = self.strands.bases(direction, window_start, start_pos);
+                scanner.scan(&upstream, upstream.len())
+            });
+
+            let kozak = match self.kozak_scoring {
+                true => {
+                    let context_start = start_pos.saturating_sub(KOZAK_UPSTREAM_LEN);
+                    let context_end = sequence_len.min(start_pos + 4);
+                    let context = self.strands.bases(direction, context_start, context_end);
+                    kozak_context(&context, start_pos - context_start)
+                }
+                false => None,
+            };
+            if let Some(min_kozak_strength) = self.min_kozak_strength {
+                let strength = kozak.as_ref().map(|kozak| kozak.strength);
+                if strength.is_none_or(|strength| strength < min_kozak_strength) {
+                    continue;
+                }
+            }
+
+            // Plain strands are shared with the ORFs, only wrapped or packed ORFs copy their bases
+            let subsequence = match start_pos <= stop_pos {
+                true => self.strands.slice(direction, start_pos, stop_pos),
+                false => {
+                    let mut wrapped = self
+                        .strands
+                        .bases(direction, start_pos, sequence_len - 2)
+                        .into_owned();
+                    wrapped.extend_from_slice(&self.strands.bases(direction, 0, stop_pos));
+                    SequenceSlice::new(Arc::from(wrapped), 0, orf_len)
+                }
+            };
+
+            let orf = ORF {
+                seqid: Arc::clone(&self.seqid),
+                start_position: start_pos + position_offset,
+                stop_position: stop_pos + position_offset,
+                sequence: subsequence.with_alphabet(self.alphabet),
+                direction: orf_positions.strand,
+                score: None,
+                rbs: rbs,
+                kozak: kozak,
+                orf_class: None,
+                variant_effect: None,
+            };
+
+            candidates.push(orf);
+        }
+
+        return select_starts(self.start_selection, candidates);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{
+        datahandler::filehandler::RecordOffset,
+        error::error::OrfFinderError,
+        finder::finder_config::FinderConfig,
+        models::models::{Direction, ORFPositions},
+    };
+
+    use super::OrfSearch;
+
+    fn circular_config(min_len: usize) -> FinderConfig {
+        return FinderConfig::builder()
+            .circular(true)
+            .min_len(min_len)
+            .build()
+            .unwrap();
+    }
+
+    #[test]
+    fn complement_sequence() {
+        let sequence = "ACTG";
+        let sequence_bytes = sequence.as_bytes().to_vec();
+
+        let complement_seq = OrfSearch::reverse_complement(&sequence_bytes).unwrap();
+        let expected_complement_seq = "CAGT".as_bytes().to_vec();
+        assert_eq!(complement_seq, expected_complement_seq);
+    }
+
+    #[test]
+    fn soft_masked_bases_are_searched_in_each_storage_mode() {
+        for packed in [false, true] {
+            let config = FinderConfig::builder()
+                .min_len(3)
+                .packed(packed)
+                .build()
+                .unwrap();
+            let search = OrfSearch::new("atgAAAtagnC".to_string(), &config).unwrap();
+
+            assert_eq!(
+                search.strands.bases(Direction::FORWARD, 0, 11).as_ref(),
+                b"ATGAAATAGNC"
+            );
+            assert_eq!(
+                search.strands.bases(Direction::REVERSE, 0, 11).as_ref(),
+                b"GNCTATTTCAT"
+            );
+        }
+    }
+
+    #[test]
+    fn rna_is_searched_as_dna() {
+        let rna = "AUGAAAUAGCCCAUGGGGCCCUAA".to_string();
+        let positions = || ORFPositions {
+            start_positions: vec![12],
+            stop_position: 21,
+            strand: Direction::FORWARD,
+        };
+
+        let search = OrfSearch::new(rna.clone(), &circular_config(3)).unwrap();
+        assert_eq!(
+            search.strands.bases(Direction::FORWARD, 0, 6).as_ref(),
+            b"ATGAAA"
+        );
+        let orfs = search.transcribe(positions());
+        assert_eq!(orfs[0].sequence.to_string(), "ATGGGGCCC");
+
+        let config = FinderConfig::builder()
+            .min_len(3)
+            .preserve_alphabet(true)
+            .build()
+            .unwrap();
+        let search = OrfSearch::new(rna, &config).unwrap();
+        let orfs = search.transcribe(positions());
+        assert_eq!(orfs[0].sequence.as_bytes(), b"ATGGGGCCC");
+        assert_eq!(orfs[0].sequence.to_string(), "AUGGGGCCC");
+    }
+
+    #[test]
+    fn invalid_base_is_reported() {
+        let search = OrfSearch::new("ACGTNARCGT".to_string(), &FinderConfig::default());
+        assert!(matches!(
+            search,
+            Err(OrfFinderError::InvalidBase {
+                base: b'R',
+                position: 6
+            })
+        ));
+    }
+
+    #[test]
+    fn transcribe_wrapping_orf() {
+        let search = OrfSearch::new("CCCTAAATGGGG".to_string(), &circular_config(0)).unwrap();
+        let orf_positions = ORFPositions {
+            start_positions: vec![6],
+            stop_position: 3,
+            strand: Direction::FORWARD,
+        };
+
+        let orfs = search.transcribe(orf_positions);
+        assert_eq!(orfs.len(), 1);
+        assert_eq!(orfs[0].sequence.as_bytes(), b"ATGGGGCCC");
+    }
+
+    #[test]
+    fn orfs_outside_length_range_are_dropped() {
+        let config = FinderConfig::builder()
+            .min_len(3)
+            .max_len(Some(10))
+            .build()
+            .unwrap();
+        let search = OrfSearch::new("ATGTTTATGTTTTAG".to_string(), &config).unwrap();
+        let orf_positions = ORFPositions {
+            start_positions: vec![0, 6, 9],
+            stop_position: 12,
+            strand: Direction::FORWARD,
+        };
+
+        let orfs = search.transcribe(orf_positions);
+        assert_eq!(orfs.len(), 1);
+        assert_eq!(orfs[0].start_position, 6);
+    }
+
+    #[test]
+    fn orfs_overlapping_masked_areas_are_dropped() {
+        // ATG TTT TAG on both strands, the forward ORF covers bases 1 to 9 and the reverse ORF bases 10 to 18
+        let sequence = "ATGTTTTAGCTAAAACAT";
+        let search = |areas: Vec<(u64, u64)>| {
+            let config = FinderConfig::builder()
+                .min_len(3)
+                .masked_areas(HashMap::from([("chr".to_string(), areas)]))
+                .build()
+                .unwrap();
+            return OrfSearch::new(sequence.to_string(), &config)
+                .unwrap()
+                .with_seqid("chr");
+        };
+        let orf_positions = |strand: Direction| ORFPositions {
+            start_positions: vec![0],
+            stop_position: 6,
+            strand: strand,
+        };
+
+        let masked = search(vec![(9, 9)]);
+        assert!(masked
+            .transcribe(orf_positions(Direction::FORWARD))
+            .is_empty());
+        assert_eq!(
+            masked.transcribe(orf_positions(Direction::REVERSE)).len(),
+            1
+        );
+
+        let masked = search(vec![(10, 10)]);
+        assert_eq!(
+            masked.transcribe(orf_positions(Direction::FORWARD)).len(),
+            1
+        );
+        assert!(masked
+            .transcribe(orf_positions(Direction::REVERSE))
+            .is_empty());
+    }
+
+    #[test]
+    fn offset_positions_are_located_on_the_source_sequence() {
+        // The region CCATGAAATAGC of the source sequence GGG CCATGAAATAGC TTTTT
+        let config = FinderConfig::builder().min_len(3).build().unwrap();
+        let search = OrfSearch::new("CCATGAAATAGC".to_string(), &config)
+            .unwrap()
+            .with_offset(RecordOffset {
+                start: 3,
+                source_len: 20,
+            });
+
+        let forward = search.transcribe(ORFPositions {
+            start_positions: vec![2],
+            stop_position: 8,
+            strand: Direction::FORWARD,
+        });
+        assert_eq!(
+            (forward[0].start_position, forward[0].stop_position),
+            (5, 11)
+        );
+
+        // The reverse strand of the region starts after the 5 bases following it
+        let reverse = search.transcribe(ORFPositions {
+            start_positions: vec![0],
+            stop_position: 6,
+            strand: Direction::REVERSE,
+        });
+        assert_eq!(
+            (reverse[0].start_position, reverse[0].stop_position),
+            (5, 11)
+        );
+    }
+
+    #[test]
+    fn short_circular_sequence() {
+        let search = OrfSearch::new("A".to_string(), &circular_config(30)).unwrap();
+        assert_eq!(
+            search.strands.bases(Direction::FORWARD, 0, 2).as_ref(),
+            b"AA"
+        );
+    }
+}
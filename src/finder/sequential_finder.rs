@@ -0,0 +1,136 @@
+use crossbeam::channel::Sender;
+
+use crate::{
+    datahandler::trans_table::CodonClass,
+    error::error::OrfFinderError,
+    models::models::{ORFPositions, ORF},
+};
+
+use super::{finder::Finder, orf_search::OrfSearch};
+
+/// Searches ORFs on the calling thread by scanning each frame of both strands from start to end.
+/// Intended for small inputs and as a reference for the parallel backends.
+pub struct SequentialFinder {
+    pub search: OrfSearch,
+}
+
+impl SequentialFinder {
+    pub fn new(search: OrfSearch) -> Self {
+        return SequentialFinder { search: search };
+    }
+
+    fn send_orfs(
+        &self,
+        orf_positions: ORFPositions,
+        orf_sender: &Sender<ORF>,
+    ) -> Result<(), OrfFinderError> {
+        for orf in self.search.transcribe(orf_positions) {
+            orf_sender.send(orf)?;
+        }
+
+        return Ok(());
+    }
+}
+
+impl Finder for SequentialFinder {
+    fn find(&self, orf_sender: Sender<ORF>) -> Result<(), OrfFinderError> {
+        for &direction in self.search.strand_selection.directions() {
+            let sequence_len = self.search.strands.len(direction);
+
+            for &offset in self.search.frame_offsets.iter() {
+                let mut starts = Vec::new();
+                let mut first_stop = None;
+
+                for i in (offset..sequence_len.saturating_sub(2)).step_by(3) {
+                    let codon = self.search.strands.codon(direction, i);
+                    match self.search.codon_lookup.classify(&codon) {
+                        CodonClass::Start => starts.push(i),
+                        CodonClass::Stop => {
+                            first_stop.get_or_insert(i);
+                            let orf_positions = ORFPositions {
+                                start_positions: std::mem::take(&mut starts),
+                                stop_position: i,
+                                strand: direction,
+                            };
+                            self.send_orfs(orf_positions, &orf_sender)?;
+                        }
+                        CodonClass::Sense => {}
+                    }
+                }
+
+                // Starts after the last stop of a circular sequence end at the first stop of the frame
+                if let (true, Some(stop_position)) = (self.search.circular, first_stop) {
+                    let orf_positions = ORFPositions {
+                        start_positions: starts,
+                        stop_position: stop_position,
+                        strand: direction,
+                    };
+                    self.send_orfs(orf_positions, &orf_sender)?;
+                }
+            }
+        }
+
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::finder::{
+        finder::Finder,
+        finder_config::FinderConfig,
+        orf_search::OrfSearch,
+        test_utils::{find_orf_coordinates, search},
+        threaded_finder::ThreadedFinder,
+    };
+
+    use super::SequentialFinder;
+
+    #[test]
+    fn sequential_matches_threaded_finder() {
+        let sequence = fs::read_to_string("resources/sequences/NC_011604.1_normal.fasta").unwrap();
+
+        for circular in [false, true] {
+            let sequential =
+                find_orf_coordinates(&SequentialFinder::new(search(&sequence, circular, 30)));
+            let mut threaded_finder = ThreadedFinder::new(search(&sequence, circular, 30), 4);
+            threaded_finder.chunk_size = 999;
+
+            assert!(!sequential.is_empty());
+            assert_eq!(sequential, find_orf_coordinates(&threaded_finder));
+        }
+    }
+
+    #[test]
+    fn packed_matches_plain_strands() {
+        let sequence = fs::read_to_string("resources/sequences/NC_011604.1_normal.fasta").unwrap();
+
+        let find = |packed: bool| {
+            let config = FinderConfig::builder()
+                .circular(true)
+                .rbs_window(Some(20))
+                .kozak_scoring(true)
+                .packed(packed)
+                .build()
+                .unwrap();
+            let search = OrfSearch::new(sequence.clone(), &config).unwrap();
+            let (send, recv) = crossbeam::channel::unbounded();
+            SequentialFinder::new(search).find(send).unwrap();
+
+            return recv
+                .iter()
+                .map(|orf| {
+                    let rbs = orf.rbs.map(|rbs| (rbs.motif, rbs.spacer));
+                    let kozak = orf.kozak.map(|kozak| kozak.context);
+                    (orf.start_position, orf.sequence.to_string(), rbs, kozak)
+                })
+                .collect::<Vec<_>>();
+        };
+
+        let plain = find(false);
+        assert!(!plain.is_empty());
+        assert_eq!(plain, find(true));
+    }
+}
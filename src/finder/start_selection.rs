@@ -0,0 +1,50 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::models::models::ORF;
+
+/// Decides which of the start codons sharing a stop codon are reported.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Serialize, Deserialize, Debug)]
+pub enum StartSelection {
+    /// Report an ORF for each start codon
+    All,
+    /// Report only the ORF of the most upstream start codon
+    Longest,
+    /// Report only the ORF with the best ribosome binding site, falls back to the longest
+    Rbs,
+    /// Report only the ORF with the best Kozak context, falls back to the longest
+    Kozak,
+}
+
+/// Selects the ORFs to report from all candidates sharing a stop codon.
+/// * `candidates` - ORFs sharing the stop codon, ordered from the most upstream start codon
+pub fn select_starts(start_selection: StartSelection, mut candidates: Vec<ORF>) -> Vec<ORF> {
+    if candidates.is_empty() {
+        return candidates;
+    }
+
+    let selected_index = match start_selection {
+        StartSelection::All => return candidates,
+        StartSelection::Longest => 0,
+        StartSelection::Rbs => best_index(&candidates, |orf| orf.rbs.as_ref().map(|rbs| rbs.score)),
+        StartSelection::Kozak => best_index(&candidates, |orf| {
+            orf.kozak.as_ref().map(|kozak| kozak.score)
+        }),
+    };
+
+    return vec![candidates.swap_remove(selected_index)];
+}
+
+/// Index of the candidate with the highest value, the most upstream candidate wins ties and if no candidate has a value.
+fn best_index<F: Fn(&ORF) -> Option<f64>>(candidates: &[ORF], value: F) -> usize {
+    let mut best: Option<(usize, f64)> = None;
+    for (index, candidate) in candidates.iter().enumerate() {
+        if let Some(candidate_value) = value(candidate) {
+            if best.is_none_or(|(_, best_value)| candidate_value > best_value) {
+                best = Some((index, candidate_value));
+            }
+        }
+    }
+
+    return best.map_or(0, |(index, _)| index);
+}
@@ -0,0 +1,23 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::models::models::Direction;
+
+/// Decides on which strands ORFs are searched, e.g. only forward for stranded transcripts.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Serialize, Deserialize, Debug)]
+pub enum StrandSelection {
+    Both,
+    Forward,
+    Reverse,
+}
+
+impl StrandSelection {
+    /// The directions of the selected strands.
+    pub fn directions(&self) -> &'static [Direction] {
+        return match self {
+            StrandSelection::Both => &[Direction::FORWARD, Direction::REVERSE],
+            StrandSelection::Forward => &[Direction::FORWARD],
+            StrandSelection::Reverse => &[Direction::REVERSE],
+        };
+    }
+}
@@ -0,0 +1,141 @@
+use std::{borrow::Cow, sync::Arc};
+
+use crate::models::{
+    models::{Direction, SequenceSlice},
+    packed_sequence::PackedSequence,
+};
+
+/// Bases of both strands of a searched sequence.
+/// The first two bases of each strand of a circular sequence are repeated at its end,
+/// so that codons spanning the end can be read.
+pub enum Strands {
+    /// One byte per base, the reverse complement is stored separately
+    Plain {
+        forward: Arc<[u8]>,
+        reverse: Arc<[u8]>,
+    },
+    /// Two bits per base of the forward strand, the reverse complement is derived while reading
+    Packed {
+        sequence: PackedSequence,
+        circular: bool,
+    },
+}
+
+impl Strands {
+    /// Number of bases of the strand, including the repeated bases of circular sequences.
+    pub fn len(&self, direction: Direction) -> usize {
+        return match self {
+            Strands::Plain { forward, reverse } => match direction {
+                Direction::FORWARD => forward.len(),
+                Direction::REVERSE => reverse.len(),
+            },
+            Strands::Packed { sequence, circular } => match circular {
+                true => sequence.len() + sequence.len().min(2),
+                false => sequence.len(),
+            },
+        };
+    }
+
+    /// The codon starting at the position of the strand.
+    #[inline(always)]
+    pub fn codon(&self, direction: Direction, position: usize) -> [u8; 3] {
+        return match self {
+            Strands::Plain { .. } => {
+                let bases = self.plain(direction);
+                [bases[position], bases[position + 1], bases[position + 2]]
+            }
+            Strands::Packed { sequence, .. } => {
+                [0, 1, 2].map(|offset| Strands::packed_base(sequence, direction, position + offset))
+            }
+        };
+    }
+
+    /// The bases from start to end of the strand, only copied for packed strands.
+    pub fn bases(&self, direction: Direction, start: usize, end: usize) -> Cow<'_, [u8]> {
+        return match self {
+            Strands::Plain { .. } => Cow::Borrowed(&self.plain(direction)[start..end]),
+            Strands::Packed { sequence, .. } => Cow::Owned(
+                (start..end)
+                    .map(|position| Strands::packed_base(sequence, direction, position))
+                    .collect(),
+            ),
+        };
+    }
+
+    /// The bases from start to end of the strand as the sequence of an ORF.
+    /// Plain strands are shared with the ORF, packed strands are unpacked.
+    pub fn slice(&self, direction: Direction, start: usize, end: usize) -> SequenceSlice {
+        return match self {
+            Strands::Plain { .. } => {
+                SequenceSlice::new(Arc::clone(self.plain(direction)), start, end)
+            }
+            Strands::Packed { .. } => {
+                let bases = self.bases(direction, start, end).into_owned();
+                SequenceSlice::new(Arc::from(bases), 0, end - start)
+            }
+        };
+    }
+
+    fn plain(&self, direction: Direction) -> &Arc<[u8]> {
+        return match (self, direction) {
+            (Strands::Plain { forward, .. }, Direction::FORWARD) => forward,
+            (Strands::Plain { reverse, .. }, Direction::REVERSE) => reverse,
+            (Strands::Packed { .. }, _) => unreachable!("packed strands are not stored per byte"),
+        };
+    }
+
+    /// Positions past the end of the sequence are the repeated bases of circular sequences.
+    #[inline(always)]
+    fn packed_base(sequence: &PackedSequence, direction: Direction, position: usize) -> u8 {
+        let position = position % sequence.len();
+        return match direction {
+            Direction::FORWARD => sequence.base(position),
+            Direction::REVERSE => sequence.complement(sequence.len() - 1 - position),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::models::{models::Direction, packed_sequence::PackedSequence};
+
+    use super::Strands;
+
+    #[test]
+    fn packed_strands_match_plain_strands() {
+        let plain = Strands::Plain {
+            forward: Arc::from(&b"ATGCCGTAGA"[..]),
+            reverse: Arc::from(&b"TCTACGGCATTC"[..]),
+        };
+        let packed = Strands::Packed {
+            sequence: PackedSequence::from_bases(b"ATGCCGTAGA").unwrap(),
+            circular: false,
+        };
+        let circular = Strands::Packed {
+            sequence: PackedSequence::from_bases(b"ATGCCGTAGA").unwrap(),
+            circular: true,
+        };
+
+        assert_eq!(packed.len(Direction::FORWARD), 10);
+        assert_eq!(circular.len(Direction::REVERSE), 12);
+        assert_eq!(
+            packed.bases(Direction::FORWARD, 0, 10),
+            plain.bases(Direction::FORWARD, 0, 10)
+        );
+        assert_eq!(
+            circular.bases(Direction::FORWARD, 0, 12).as_ref(),
+            b"ATGCCGTAGAAT"
+        );
+        assert_eq!(
+            circular.bases(Direction::REVERSE, 0, 12),
+            plain.bases(Direction::REVERSE, 0, 12)
+        );
+        assert_eq!(packed.codon(Direction::REVERSE, 3), *b"ACG");
+        assert_eq!(
+            packed.slice(Direction::REVERSE, 1, 7).as_bytes(),
+            plain.slice(Direction::REVERSE, 1, 7).as_bytes()
+        );
+    }
+}
@@ -0,0 +1,31 @@
+use crate::models::models::Direction;
+
+use super::{finder::Finder, finder_config::FinderConfig, orf_search::OrfSearch};
+
+/// Prepares a search of both strands for ORFs of at least `min_len` bases
+pub fn search(sequence: &str, circular: bool, min_len: usize) -> OrfSearch {
+    let config = FinderConfig::builder()
+        .circular(circular)
+        .min_len(min_len)
+        .build()
+        .unwrap();
+
+    return OrfSearch::new(sequence.to_string(), &config).unwrap();
+}
+
+/// Runs the finder and returns the coordinates of all ORFs in a stable order
+pub fn find_orf_coordinates(finder: &dyn Finder) -> Vec<(usize, usize, bool)> {
+    let (send, recv) = crossbeam::channel::unbounded();
+    finder.find(send).unwrap();
+
+    let mut coordinates: Vec<_> = recv
+        .iter()
+        .map(|orf| {
+            let is_reverse = orf.direction == Direction::REVERSE;
+            (orf.start_position, orf.stop_position, is_reverse)
+        })
+        .collect();
+    coordinates.sort();
+
+    return coordinates;
+}
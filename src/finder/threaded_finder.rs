@@ -1,309 +1,426 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use crate::{
-    datahandler::{self, trans_table::TranslationalTable},
+    datahandler::trans_table::CodonClass,
+    error::error::{root_cause, OrfFinderError},
     models::models::{Direction, ORFPositions, ORF},
-    outwriter::outwriter::OutWriter,
 };
-use crossbeam::channel::{bounded, Receiver, Sender};
+
+use super::{finder::Finder, orf_search::OrfSearch};
+use crossbeam::channel::{bounded, unbounded, Receiver, Sender};
 use crossbeam::thread;
 
-const U8_A: u8 = 'A' as u8;
-const U8_C: u8 = 'C' as u8;
-const U8_T: u8 = 'T' as u8;
-const U8_G: u8 = 'G' as u8;
+/// Default number of bases of a frame scanned at once by a single finder thread
+pub const DEFAULT_CHUNK_SIZE: usize = 1_000_000;
+
+/// Splits the threads into finder and transcriber threads, at least 2 threads are required.
+/// Half of them search ORFs, but never more than there are chunks to scan, all others transcribe.
+fn split_threads(num_threads: usize, num_chunks: usize) -> (usize, usize) {
+    let finder_threads = (num_threads / 2).clamp(1, num_chunks.max(1));
+
+    return (finder_threads, num_threads - finder_threads);
+}
 
-enum NextThreadType {
-    Finder,
-    Transcriber,
+/// A part of a frame scanned by a single finder thread
+struct Chunk {
+    direction: Direction,
+    offset: usize,
+    index: usize,
+    /// Position of the first codon
+    start: usize,
+    /// Exclusive bound of the codon positions
+    end: usize,
 }
 
+/// Starts and stops of a chunk that can only be paired across chunk borders
+struct ChunkBoundary {
+    direction: Direction,
+    offset: usize,
+    index: usize,
+    /// Starts before the first stop, or all starts if the chunk contains no stop
+    leading_starts: Vec<usize>,
+    first_stop: Option<usize>,
+    /// Starts after the last stop
+    trailing_starts: Vec<usize>,
+}
+
+/// Searches ORFs in parallel, each frame of both strands is split into chunks scanned by the finder threads,
+/// the transcriber threads turn the found start and stop positions into ORFs.
 pub struct ThreadedFinder {
-    pub fw_sequence: String,
-    pub rev_sequence: String,
-    pub masked_areas: HashMap<u64, u64>,
-    pub translational_table: TranslationalTable,
-    pub outwriter: Box<dyn OutWriter + Send + Sync + 'static>,
-    pub min_len: usize,
-    pub circular: bool,
+    pub search: OrfSearch,
+    /// Number of finder and transcriber threads
+    pub num_threads: usize,
+    /// Number of bases of a frame scanned at once by a single finder thread
+    pub chunk_size: usize,
 }
 
 impl ThreadedFinder {
-    pub fn new(
-        sequence: String,
-        translational_table_id: u8,
-        masked_areas: HashMap<u64, u64>,
-        circular: bool,
-        min_len: usize,
-        outwriter: Box<dyn OutWriter + Send + Sync + 'static>,
-    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
-        let table =
-            datahandler::trans_table::parse_translational_table(translational_table_id)?.unwrap();
-
-        let rev_seq: String = sequence.chars().rev().collect();
-        let mut fw_seq_bytes = sequence.as_bytes().to_vec();
-        let rev_seq_bytes = rev_seq.as_bytes().to_vec();
-        let mut rev_complement_seq_bytes =
-            ThreadedFinder::sequence_complement(rev_seq_bytes).unwrap();
-
-        if circular {
-            fw_seq_bytes = ThreadedFinder::append_two_bases(fw_seq_bytes);
-            rev_complement_seq_bytes = ThreadedFinder::append_two_bases(rev_complement_seq_bytes);
-        }
-
-        let final_fw_sequence = std::str::from_utf8(&fw_seq_bytes).unwrap().to_string();
-        let final_rev_sequence = std::str::from_utf8(&rev_complement_seq_bytes)
-            .unwrap()
-            .to_string();
-
-        let finder = ThreadedFinder {
-            fw_sequence: final_fw_sequence,
-            rev_sequence: final_rev_sequence,
-            translational_table: table,
-            masked_areas: masked_areas,
-            min_len: min_len,
-            circular: circular,
-            outwriter: outwriter,
+    pub fn new(search: OrfSearch, num_threads: usize) -> Self {
+        return ThreadedFinder {
+            search: search,
+            num_threads: num_threads,
+            chunk_size: DEFAULT_CHUNK_SIZE,
         };
-
-        return Ok(finder);
     }
 
-    #[inline(always)]
-    fn append_two_bases(mut sequence: Vec<u8>) -> Vec<u8> {
-        let first = sequence.get(0).unwrap().clone();
-        let second = sequence.get(1).unwrap().clone();
-
-        sequence.push(first);
-        sequence.push(second);
-
-        return sequence;
+    /// Scans all chunks and transcribes the ORFs one after another on the calling thread.
+    fn find_sequential(&self, orf_sender: Sender<ORF>) -> Result<(), OrfFinderError> {
+        let (orf_positions_sender, orf_positions_recv) = unbounded();
+        let chunk_boundaries = self
+            .chunks()
+            .into_iter()
+            .map(|chunk| self.orf_reader(chunk, orf_positions_sender.clone()))
+            .collect::<Result<_, _>>()?;
+        self.stitch_chunks(chunk_boundaries, &orf_positions_sender)?;
+        drop(orf_positions_sender);
+
+        return self.transcribe_orfs(orf_positions_recv, orf_sender);
     }
 
-    #[inline(always)]
-    fn sequence_complement(
-        sequence: Vec<u8>,
-    ) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
-        let mut complement_seq = Vec::with_capacity(sequence.len());
-        for base in sequence {
-            let complement_base = ThreadedFinder::complement(base).unwrap();
-            complement_seq.push(complement_base);
-        }
-
-        return Ok(complement_seq);
-    }
-
-    #[inline(always)]
-    fn complement(base: u8) -> Result<u8, Box<dyn std::error::Error + Send + Sync>> {
-        let complement_base = match base {
-            U8_A => U8_T,
-            U8_T => U8_A,
-            U8_C => U8_G,
-            U8_G => U8_C,
-            _ => {
-                panic!("could not match selected base")
-            }
-        };
-
-        return Ok(complement_base);
-    }
-
-    pub fn run(&self, num_threads: u8) {
-        let (orf_positions_sender, orf_positions_recv) = bounded(100);
-        let (orf_sender, orf_receiver) = bounded(100);
-        let (orf_reader_start_pos_sender, orf_reader_start_pos_recv) = bounded(6);
-
-        for i in 0..3 {
-            orf_reader_start_pos_sender
-                .send((i, Direction::FORWARD))
-                .unwrap();
-            orf_reader_start_pos_sender
-                .send((i, Direction::REVERSE))
-                .unwrap();
-        }
-
-        let mut next_thread_type = NextThreadType::Finder;
-
-        thread::scope(|s| {
-            let cloned_orf_receiver = orf_receiver.clone();
-            s.spawn(|_| self.outwriter.write(cloned_orf_receiver));
-            for _ in 0..num_threads - 1 {
-                match next_thread_type {
-                    NextThreadType::Finder => {
-                        let cloned_orf_reader_start_pos_recv = orf_reader_start_pos_recv.clone();
-                        let cloned_orf_positions_sender = orf_positions_sender.clone();
-
-                        s.spawn(|_| {
-                            self.find_orfs(
-                                cloned_orf_reader_start_pos_recv,
-                                cloned_orf_positions_sender,
-                            )
-                        });
-                        next_thread_type = NextThreadType::Transcriber;
-                    }
-                    NextThreadType::Transcriber => {
-                        let cloned_orf_positions_recv = orf_positions_recv.clone();
-                        let cloned_orf_sender = orf_sender.clone();
-
-                        s.spawn(|_| {
-                            self.transcribe_orfs(cloned_orf_positions_recv, cloned_orf_sender)
-                        });
-
-                        next_thread_type = NextThreadType::Finder;
-                    }
+    /// Splits each frame of both strands into chunks of at most `chunk_size` bases.
+    fn chunks(&self) -> Vec<Chunk> {
+        let chunk_len = (self.chunk_size / 3).max(1) * 3;
+        let mut chunks = Vec::new();
+
+        for &offset in self.search.frame_offsets.iter() {
+            for &direction in self.search.strand_selection.directions() {
+                let sequence_len = self.search.strands.len(direction);
+                // Exclusive bound of the codon positions, the last codon has to fit into the sequence
+                let codons_end = sequence_len.saturating_sub(2);
+
+                for (index, start) in (offset..codons_end).step_by(chunk_len).enumerate() {
+                    chunks.push(Chunk {
+                        direction: direction,
+                        offset: offset,
+                        index: index,
+                        start: start,
+                        end: codons_end.min(start.saturating_add(chunk_len)),
+                    });
                 }
             }
+        }
 
-            drop(orf_reader_start_pos_sender);
-            drop(orf_reader_start_pos_recv);
-            drop(orf_positions_sender);
-            drop(orf_positions_recv);
-            drop(orf_sender);
-            drop(orf_receiver);
-        })
-        .unwrap();
+        return chunks;
     }
 
     fn find_orfs(
         &self,
-        orf_reader_start_pos_recv: Receiver<(u64, Direction)>,
+        orf_reader_chunk_recv: Receiver<Chunk>,
         orf_positions_sender: Sender<ORFPositions>,
-    ) {
-        for start_pos in orf_reader_start_pos_recv {
-            self.orf_reader(start_pos.0, start_pos.1, orf_positions_sender.clone());
+        chunk_boundary_sender: Sender<ChunkBoundary>,
+    ) -> Result<(), OrfFinderError> {
+        for chunk in orf_reader_chunk_recv {
+            let chunk_boundary = self.orf_reader(chunk, orf_positions_sender.clone())?;
+            chunk_boundary_sender.send(chunk_boundary)?;
         }
-    }
 
-    fn orf_reader(&self, offset: u64, direction: Direction, sender: Sender<ORFPositions>) {
-        let sequence: &String;
-        match direction {
-            Direction::FORWARD => sequence = &self.fw_sequence,
-            Direction::REVERSE => sequence = &self.rev_sequence,
-        }
+        return Ok(());
+    }
 
+    /// Sends all ORFs that are completely located in the chunk and returns the starts and stops that
+    /// have to be paired with the neighbouring chunks.
+    fn orf_reader(
+        &self,
+        chunk: Chunk,
+        sender: Sender<ORFPositions>,
+    ) -> Result<ChunkBoundary, OrfFinderError> {
         let mut starts = Vec::new();
+        let mut leading_starts = Vec::new();
+        let mut first_stop = None;
+
+        for i in (chunk.start..chunk.end).step_by(3) {
+            let codon = self.search.strands.codon(chunk.direction, i);
+            match self.search.codon_lookup.classify(&codon) {
+                CodonClass::Start => starts.push(i),
+                CodonClass::Stop => {
+                    if first_stop.is_none() {
+                        first_stop = Some(i);
+                        leading_starts = std::mem::take(&mut starts);
+                        continue;
+                    }
 
-        for n in (offset..sequence.len() as u64).step_by(3) {
-            if n + 2 >= sequence.len() as u64 {
-                break;
-            }
+                    let orf = ORFPositions {
+                        start_positions: std::mem::take(&mut starts),
+                        stop_position: i,
+                        strand: chunk.direction,
+                    };
 
-            let i = n as usize;
-            let codon = sequence[i..i + 3].to_string();
-            if self.translational_table.start_codons.contains(&codon) {
-                starts.push(n as usize);
+                    sender.send(orf)?;
+                }
+                CodonClass::Sense => {}
             }
+        }
 
-            if self.translational_table.stop_codons.contains(&codon) {
-                let orf = ORFPositions {
-                    start_positions: starts,
-                    stop_position: n as usize,
-                    strand: direction,
-                };
+        if first_stop.is_none() {
+            leading_starts = std::mem::take(&mut starts);
+        }
 
-                starts = Vec::new();
+        return Ok(ChunkBoundary {
+            direction: chunk.direction,
+            offset: chunk.offset,
+            index: chunk.index,
+            leading_starts: leading_starts,
+            first_stop: first_stop,
+            trailing_starts: starts,
+        });
+    }
 
-                sender.send(orf).unwrap();
-            }
+    /// Pairs the open starts of each chunk with the first stop of the following chunks.
+    /// For circular sequences the starts after the last stop of a frame are paired with its first stop.
+    fn stitch_chunks(
+        &self,
+        chunk_boundaries: Vec<ChunkBoundary>,
+        sender: &Sender<ORFPositions>,
+    ) -> Result<(), OrfFinderError> {
+        let mut frames: BTreeMap<(bool, usize), Vec<ChunkBoundary>> = BTreeMap::new();
+        for chunk_boundary in chunk_boundaries {
+            let is_reverse = chunk_boundary.direction == Direction::REVERSE;
+            frames
+                .entry((is_reverse, chunk_boundary.offset))
+                .or_default()
+                .push(chunk_boundary);
         }
 
-        if self.circular {
-            for n in (offset..sequence.len() as u64).step_by(3) {
-                if n + 2 >= sequence.len() as u64 {
-                    break;
-                }
+        for mut frame in frames.into_values() {
+            frame.sort_by_key(|chunk_boundary| chunk_boundary.index);
+
+            let mut open_starts = Vec::new();
+            let mut frame_first_stop = None;
+            let mut direction = Direction::FORWARD;
+            for chunk_boundary in frame {
+                direction = chunk_boundary.direction;
+                open_starts.extend(chunk_boundary.leading_starts);
 
-                let i = n as usize;
-                let codon = sequence[i..i + 3].to_string();
-                if self.translational_table.stop_codons.contains(&codon) {
+                if let Some(stop_position) = chunk_boundary.first_stop {
                     let orf = ORFPositions {
-                        start_positions: starts,
-                        stop_position: n as usize,
+                        start_positions: open_starts,
+                        stop_position: stop_position,
                         strand: direction,
                     };
+                    sender.send(orf)?;
 
-                    sender.send(orf).unwrap();
+                    frame_first_stop.get_or_insert(stop_position);
+                    open_starts = chunk_boundary.trailing_starts;
+                }
+            }
 
-                    break;
+            if self.search.circular {
+                if let Some(stop_position) = frame_first_stop {
+                    let orf = ORFPositions {
+                        start_positions: open_starts,
+                        stop_position: stop_position,
+                        strand: direction,
+                    };
+                    sender.send(orf)?;
                 }
             }
         }
+
+        return Ok(());
     }
 
-    fn transcribe_orfs(&self, orf_positions_recv: Receiver<ORFPositions>, orf_sender: Sender<ORF>) {
+    fn transcribe_orfs(
+        &self,
+        orf_positions_recv: Receiver<ORFPositions>,
+        orf_sender: Sender<ORF>,
+    ) -> Result<(), OrfFinderError> {
         for orf_positions in orf_positions_recv.iter() {
-            let sequence: String;
-            match orf_positions.strand {
-                Direction::FORWARD => sequence = self.fw_sequence.clone(),
-                Direction::REVERSE => sequence = self.rev_sequence.clone(),
+            for orf in self.search.transcribe(orf_positions) {
+                orf_sender.send(orf)?;
             }
+        }
 
-            for start_pos in orf_positions.start_positions {
-                let diff = orf_positions.stop_position - start_pos;
-                if diff <= self.min_len {
-                    continue;
-                }
+        return Ok(());
+    }
+}
 
-                let subsequence = &sequence.as_bytes()[start_pos..orf_positions.stop_position];
-                let subsequence_string = std::str::from_utf8(subsequence).unwrap().to_string();
+impl Finder for ThreadedFinder {
+    fn find(&self, orf_sender: Sender<ORF>) -> Result<(), OrfFinderError> {
+        if self.num_threads < 2 {
+            return self.find_sequential(orf_sender);
+        }
 
-                let orf = ORF {
-                    start_position: start_pos as usize,
-                    stop_position: orf_positions.stop_position as usize,
-                    sequence: subsequence_string,
-                    direction: orf_positions.strand,
-                };
+        let (orf_positions_sender, orf_positions_recv) = bounded(100);
+        let (chunk_boundary_sender, chunk_boundary_recv) = unbounded();
 
-                orf_sender.send(orf).unwrap();
-            }
+        let chunks = self.chunks();
+        let (finder_threads, transcriber_threads) = split_threads(self.num_threads, chunks.len());
+        let (orf_reader_chunk_sender, orf_reader_chunk_recv) = bounded(chunks.len());
+        for chunk in chunks {
+            orf_reader_chunk_sender.send(chunk)?;
         }
+        drop(orf_reader_chunk_sender);
+
+        let results = thread::scope(|s| {
+            let mut handles = Vec::new();
+            for _ in 0..finder_threads {
+                let cloned_orf_reader_chunk_recv = orf_reader_chunk_recv.clone();
+                let cloned_orf_positions_sender = orf_positions_sender.clone();
+                let cloned_chunk_boundary_sender = chunk_boundary_sender.clone();
+
+                handles.push(s.spawn(|_| {
+                    self.find_orfs(
+                        cloned_orf_reader_chunk_recv,
+                        cloned_orf_positions_sender,
+                        cloned_chunk_boundary_sender,
+                    )
+                }));
+            }
+
+            for _ in 0..transcriber_threads {
+                let cloned_orf_positions_recv = orf_positions_recv.clone();
+                let cloned_orf_sender = orf_sender.clone();
+
+                handles.push(
+                    s.spawn(|_| self.transcribe_orfs(cloned_orf_positions_recv, cloned_orf_sender)),
+                );
+            }
+
+            drop(orf_reader_chunk_recv);
+            drop(orf_positions_recv);
+            drop(orf_sender);
+
+            // The boundaries are complete once all finder threads are done
+            drop(chunk_boundary_sender);
+            let chunk_boundaries = chunk_boundary_recv.iter().collect();
+            let mut results = vec![self.stitch_chunks(chunk_boundaries, &orf_positions_sender)];
+            drop(orf_positions_sender);
+
+            for handle in handles {
+                let result = handle
+                    .join()
+                    .unwrap_or_else(|panic| Err(OrfFinderError::from_panic(panic)));
+                results.push(result);
+            }
+
+            results
+        })
+        .map_err(OrfFinderError::from_panic)?;
+
+        return root_cause(results);
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::{collections::HashMap, fs};
-
-    use crate::outwriter::{channel_writer::ChannelWriter, count_writer::CountWriter};
+    use std::fs;
+
+    use crate::{
+        finder::{
+            finder::run_pipeline,
+            finder_config::FinderConfig,
+            orf_search::OrfSearch,
+            strand_selection::StrandSelection,
+            test_utils::{find_orf_coordinates, search},
+        },
+        models::models::Direction,
+        outwriter::count_writer::CountWriter,
+    };
+
+    use super::{split_threads, ThreadedFinder};
+
+    /// Scans all chunks sequentially and returns the stitched ORF positions in a stable order
+    fn find_orf_positions(
+        sequence: &str,
+        circular: bool,
+        chunk_size: usize,
+    ) -> Vec<(bool, usize, Vec<usize>)> {
+        let mut finder = ThreadedFinder::new(search(sequence, circular, 30), 1);
+        finder.chunk_size = chunk_size;
 
-    use super::ThreadedFinder;
+        let (send, recv) = crossbeam::channel::unbounded();
+        let chunk_boundaries = finder
+            .chunks()
+            .into_iter()
+            .map(|chunk| finder.orf_reader(chunk, send.clone()).unwrap())
+            .collect();
+        finder.stitch_chunks(chunk_boundaries, &send).unwrap();
+
+        let mut orf_positions: Vec<_> = recv
+            .try_iter()
+            .map(|positions| {
+                let is_reverse = positions.strand == Direction::REVERSE;
+                (
+                    is_reverse,
+                    positions.stop_position,
+                    positions.start_positions,
+                )
+            })
+            .collect();
+        orf_positions.sort();
+
+        return orf_positions;
+    }
 
     #[test]
     fn threaded_finder_full() {
-        let count_writer = Box::new(CountWriter {});
-        let masked_areas = HashMap::new();
+        let count_writer = CountWriter::new(std::io::sink());
         let sequence = fs::read_to_string("resources/sequences/NC_011604.1_normal.fasta").unwrap();
-        let threaded_finder =
-            ThreadedFinder::new(sequence, 11, masked_areas, false, 30, count_writer).unwrap();
-        threaded_finder.run(4);
+        let threaded_finder = ThreadedFinder::new(search(&sequence, false, 30), 3);
+        run_pipeline(&threaded_finder, &[], &count_writer).unwrap();
     }
+
+    #[test]
     fn simple_orf() {
-        let (send, recv) = crossbeam::channel::unbounded();
+        let finder = ThreadedFinder::new(search("ATGTTTATTTTTTAG", false, 1), 3);
+        let orfs = find_orf_coordinates(&finder);
+
+        assert_eq!(orfs, vec![(0, 12, false), (6, 12, false)]);
+    }
 
-        let channel_writer = Box::new(ChannelWriter::new(send, recv.clone()));
-        let sequence = "ATGTTTATTTTTTAG".to_string();
-        let masked_areas = HashMap::new();
-        let finder =
-            ThreadedFinder::new(sequence, 11, masked_areas, false, 1, channel_writer).unwrap();
-        finder.run(4);
-        let mut orfs = Vec::new();
-        for orf in recv {
-            orfs.push(orf);
+    #[test]
+    fn single_thread_matches_multiple_threads() {
+        let sequence = fs::read_to_string("resources/sequences/NC_011604.1_normal.fasta").unwrap();
+
+        let multi_threaded =
+            find_orf_coordinates(&ThreadedFinder::new(search(&sequence, false, 30), 7));
+        for num_threads in [1, 2, 3] {
+            let finder = ThreadedFinder::new(search(&sequence, false, 30), num_threads);
+            assert_eq!(find_orf_coordinates(&finder), multi_threaded);
         }
+    }
+
+    #[test]
+    fn restricted_search_finds_subset() {
+        let sequence = fs::read_to_string("resources/sequences/NC_011604.1_normal.fasta").unwrap();
+        let all = find_orf_coordinates(&ThreadedFinder::new(search(&sequence, true, 30), 4));
+
+        let config = FinderConfig::builder()
+            .circular(true)
+            .strand(StrandSelection::Reverse)
+            .frames(vec![2, 3])
+            .build()
+            .unwrap();
+        let search = OrfSearch::new(sequence, &config).unwrap();
+        let restricted = find_orf_coordinates(&ThreadedFinder::new(search, 4));
+
+        // Frames 2 and 3 of the reverse strand start at offsets 1 and 2
+        let expected: Vec<_> = all
+            .into_iter()
+            .filter(|(start, _, is_reverse)| *is_reverse && start % 3 != 0)
+            .collect();
+        assert!(!expected.is_empty());
+        assert_eq!(restricted, expected);
+    }
 
-        assert_eq!(orfs.get(0).unwrap().start_position, 0);
-        assert_eq!(orfs.get(1).unwrap().start_position, 2);
-        assert_eq!(orfs.get(0).unwrap().stop_position, 5);
-        assert_eq!(orfs.get(1).unwrap().stop_position, 5);
+    #[test]
+    fn thread_split() {
+        assert_eq!(split_threads(2, 6), (1, 1));
+        assert_eq!(split_threads(3, 6), (1, 2));
+        assert_eq!(split_threads(8, 6), (4, 4));
+        assert_eq!(split_threads(31, 6), (6, 25));
     }
 
     #[test]
-    fn complement_sequence() {
-        let sequence = "ACTG";
-        let sequence_bytes = sequence.as_bytes().to_vec();
+    fn chunked_scan_matches_unchunked_scan() {
+        let sequence = fs::read_to_string("resources/sequences/NC_011604.1_normal.fasta").unwrap();
 
-        let complement_seq = ThreadedFinder::sequence_complement(sequence_bytes).unwrap();
-        let expected_complement_seq = "TGAC".as_bytes().to_vec();
-        assert_eq!(complement_seq, expected_complement_seq);
+        for circular in [false, true] {
+            let unchunked = find_orf_positions(&sequence, circular, usize::MAX);
+            for chunk_size in [3, 100, 1000, 4000] {
+                let chunked = find_orf_positions(&sequence, circular, chunk_size);
+                assert_eq!(unchunked, chunked);
+            }
+        }
     }
 }
@@ -1,41 +1,409 @@
 use std::collections::HashMap;
 
-use outwriter::outwriter::{get_writer, OutputType};
+use datahandler::{
+    filehandler::SequenceRecord, transcript_structure::TranscriptStructure, vcf::Variant,
+};
+use error::error::OrfFinderError;
+use finder::{
+    finder::run_search, finder_config::FinderConfig, orf_iter::OrfIter, orf_search::OrfSearch,
+};
+use models::models::{Alphabet, ORF};
+use outwriter::{
+    channel_writer::ChannelWriter,
+    outwriter::{get_writer, OutWriter, OutputType},
+    spliced_gff_writer::SplicedGffWriter,
+    transcriptome_writer::TranscriptomeWriter,
+};
+use postprocessor::{
+    forward_strand_mapper::ForwardStrandMapper,
+    overlap_resolver::{
+        OverlapResolver, DEFAULT_MAX_OPPOSITE_STRAND_OVERLAP, DEFAULT_MAX_SAME_STRAND_OVERLAP,
+    },
+    postprocessor::PostProcessor,
+    transcript_selector::TranscriptSelector,
+};
+use scoring::{gene_model::GeneModel, rbs::DEFAULT_RBS_WINDOW};
+use variants::{orf_comparison::compare_orfs, variant_sequence::VariantSequence};
 
 pub mod datahandler;
+pub mod error;
 pub mod finder;
-mod models;
+pub mod models;
 pub mod outwriter;
+pub mod postprocessor;
+pub mod scoring;
+pub mod variants;
+
+#[cfg(test)]
+pub(crate) mod test_utils;
 
 /// Finds all open reading frame (ORFs) using a multithreaded approach.
-/// At least 4 threads are required.
+/// With less than 3 threads the sequential finder is used and all stages run one after another.
 /// * `sequence` - The sequence on which to search ORFs on
-/// * `masked_areas` - A map of masked areas in the sequence that is not relevant
-/// * `threads` - Number of threads to use, at least 4 are required
-/// * `circular` - Indicates if the sequence is circular
-/// * `trans_table` - Number of the translational table to use - this will change in a future update
+/// * `config` - The settings of the search, recorded in the output header
 /// * `out_format` - The output format in which the results should be written in
 /// * `out_target`- The output target which to write the results to
+/// * `postprocessors` - Stages applied in order to the found ORFs before they are written, e.g. overlap resolution
+///
+/// Fails if the sequence is invalid, the configuration is invalid or a stage of the pipeline fails.
 pub fn find_orfs(
     sequence: String,
-    masked_areas: HashMap<u64, u64>,
-    threads: u8,
-    circular: bool,
-    trans_table: u8,
-    min_len: usize,
+    config: &FinderConfig,
+    out_format: OutputType,
+    out_target: Box<dyn std::io::Write + Send + Sync>,
+    mut postprocessors: Vec<Box<dyn PostProcessor + Send + Sync>>,
+) -> Result<(), OrfFinderError> {
+    config.validate()?;
+    let writer = get_writer(out_format, out_target, config);
+    if out_format == OutputType::GFF3 {
+        postprocessors.push(Box::new(ForwardStrandMapper::new(sequence.len())));
+    }
+    let search = OrfSearch::new(sequence, config)?;
+
+    run_search(search, config.threads, &postprocessors, writer.as_ref())?;
+
+    return writer.finish();
+}
+
+/// Finds the ORFs of each record one after another and writes them with a single writer.
+/// Only the record currently searched is held in memory, so ORFs are written while the input is still read.
+/// Returns the number of searched records.
+/// * `records` - The sequence records, e.g. streamed by `read_records`
+/// * `config` - The settings of the search, recorded in the output header
+/// * `out_format` - The output format in which the results should be written in
+/// * `out_target`- The output target which to write the results to
+/// * `postprocessors` - Creates the post processors for each record, as they may depend on its sequence
+pub fn find_orfs_in_records<R, P>(
+    records: R,
+    config: &FinderConfig,
+    out_format: OutputType,
+    out_target: Box<dyn std::io::Write + Send + Sync>,
+    mut postprocessors: P,
+) -> Result<usize, OrfFinderError>
+where
+    R: IntoIterator<Item = Result<SequenceRecord, OrfFinderError>>,
+    P: FnMut(&SequenceRecord) -> Result<Vec<Box<dyn PostProcessor + Send + Sync>>, OrfFinderError>,
+{
+    config.validate()?;
+    let writer = get_writer(out_format, out_target, config);
+
+    let mut num_records = 0;
+    for record in records {
+        let record = record?;
+        let mut record_postprocessors = postprocessors(&record)?;
+        // GFF3 positions are located on the forward strand, reverse ORFs of regions on the whole sequence
+        if out_format == OutputType::GFF3 {
+            let source_len = record
+                .offset
+                .map_or(record.len(), |offset| offset.source_len);
+            record_postprocessors.push(Box::new(ForwardStrandMapper::new(source_len)));
+        }
+        search_record(record, config, &record_postprocessors, writer.as_ref())?;
+        num_records += 1;
+    }
+    writer.finish()?;
+
+    return Ok(num_records);
+}
+
+/// Finds the best candidate coding regions of each transcript, see `TranscriptSelector`,
+/// and writes them in the formats of TransDecoder. Returns the number of searched transcripts.
+/// * `records` - The transcripts, e.g. streamed by `read_records`
+/// * `config` - The settings of the search, transcripts can not be circular
+/// * `max_orfs` - Maximum number of ORFs reported per transcript
+/// * `outputs` - The outputs to write the ORFs to, e.g. created by `TranscriptomeWriter::create`
+/// * `postprocessors` - Creates the post processors for each transcript, applied before the selection, e.g. scoring
+pub fn find_transcript_orfs<R, P, T>(
+    records: R,
+    config: &FinderConfig,
+    max_orfs: usize,
+    outputs: &TranscriptomeWriter<T>,
+    mut postprocessors: P,
+) -> Result<usize, OrfFinderError>
+where
+    R: IntoIterator<Item = Result<SequenceRecord, OrfFinderError>>,
+    P: FnMut(&SequenceRecord) -> Result<Vec<Box<dyn PostProcessor + Send + Sync>>, OrfFinderError>,
+    T: std::io::Write + Send,
+{
+    config.validate()?;
+    if config.circular {
+        return Err(OrfFinderError::InvalidConfig(
+            "transcripts can not be circular".to_string(),
+        ));
+    }
+    if max_orfs == 0 {
+        return Err(OrfFinderError::InvalidConfig(
+            "at least one ORF has to be reported per transcript".to_string(),
+        ));
+    }
+
+    let mut num_records = 0;
+    for record in records {
+        let record = record?;
+        let mut record_postprocessors = postprocessors(&record)?;
+        record_postprocessors.push(Box::new(TranscriptSelector::new(max_orfs)));
+        // ORFs of regions are located on the whole transcript
+        let transcript_len = record
+            .offset
+            .map_or(record.len(), |offset| offset.source_len);
+        let writer = outputs.transcript(transcript_len);
+        search_record(record, config, &record_postprocessors, &writer)?;
+        num_records += 1;
+    }
+
+    return Ok(num_records);
+}
+
+/// Builds the spliced transcripts from the genome, finds their ORFs and writes them as CDS features on the genome.
+/// Only the genome sequence currently spliced is held in memory. Returns the number of searched transcripts.
+/// * `genome` - The genome sequences, e.g. streamed by `read_records`
+/// * `transcripts` - The exons of the transcripts, e.g. read by `parse_transcript_structure_file`
+/// * `config` - The settings of the search, transcripts can not be circular
+/// * `outputs` - The GFF3 output the projected ORFs are written to
+/// * `postprocessors` - Creates the post processors for each spliced transcript, e.g. scoring
+///
+/// Fails if a transcript is located on a sequence that is not part of the genome.
+pub fn find_spliced_orfs<R, P, T>(
+    genome: R,
+    transcripts: &[TranscriptStructure],
+    config: &FinderConfig,
+    outputs: &SplicedGffWriter<T>,
+    mut postprocessors: P,
+) -> Result<usize, OrfFinderError>
+where
+    R: IntoIterator<Item = Result<SequenceRecord, OrfFinderError>>,
+    P: FnMut(&SequenceRecord) -> Result<Vec<Box<dyn PostProcessor + Send + Sync>>, OrfFinderError>,
+    T: std::io::Write + Send,
+{
+    config.validate()?;
+    if config.circular {
+        return Err(OrfFinderError::InvalidConfig(
+            "transcripts can not be circular".to_string(),
+        ));
+    }
+
+    let mut transcripts_by_seqid: HashMap<&str, Vec<&TranscriptStructure>> = HashMap::new();
+    for transcript in transcripts {
+        transcripts_by_seqid
+            .entry(transcript.seqid.as_str())
+            .or_default()
+            .push(transcript);
+    }
+
+    let mut num_transcripts = 0;
+    for genome_record in genome {
+        let genome_record = genome_record?;
+        let record_transcripts = match transcripts_by_seqid.remove(genome_record.id.as_str()) {
+            Some(record_transcripts) => record_transcripts,
+            None => continue,
+        };
+
+        let genome_bases = genome_record.bases();
+        for transcript in record_transcripts {
+            let record = SequenceRecord {
+                id: transcript.id.clone(),
+                sequence: transcript.splice(&genome_bases)?,
+                packed: None,
+                offset: None,
+            };
+            let record_postprocessors = postprocessors(&record)?;
+            let writer = outputs.transcript(transcript);
+            search_record(record, config, &record_postprocessors, &writer)?;
+            num_transcripts += 1;
+        }
+    }
+
+    if let Some(seqid) = transcripts_by_seqid.keys().next() {
+        return Err(OrfFinderError::AnnotationParse(format!(
+            "the transcript sequence {} is not part of the genome",
+            seqid
+        )));
+    }
+
+    return Ok(num_transcripts);
+}
+
+/// Applies the variants to each record and writes the ORFs that differ between the reference and the variant sequence,
+/// see `compare_orfs`. Both sequences are searched with the same configuration and post processors.
+/// Records without variants are not searched and yield no ORFs. Returns the number of read records.
+/// * `records` - The reference sequences, e.g. streamed by `read_records`
+/// * `variants` - The variants to apply, e.g. read by `parse_vcf_file`
+/// * `config` - The settings of the search, the references can not be circular
+/// * `out_format` - The output format in which the results should be written in
+/// * `out_target`- The output target which to write the results to
+/// * `postprocessors` - Creates the post processors for each sequence, as they may depend on it
+pub fn find_variant_orfs<R, P>(
+    records: R,
+    variants: &[Variant],
+    config: &FinderConfig,
     out_format: OutputType,
     out_target: Box<dyn std::io::Write + Send + Sync>,
-) {
-    let writer = get_writer(out_format, out_target);
-
-    let finder = finder::threaded_finder::ThreadedFinder::new(
-        sequence.to_string(),
-        trans_table,
-        masked_areas.clone(),
-        circular,
-        min_len,
-        writer,
-    )
-    .unwrap();
-    finder.run(threads);
+    mut postprocessors: P,
+) -> Result<usize, OrfFinderError>
+where
+    R: IntoIterator<Item = Result<SequenceRecord, OrfFinderError>>,
+    P: FnMut(&SequenceRecord) -> Result<Vec<Box<dyn PostProcessor + Send + Sync>>, OrfFinderError>,
+{
+    config.validate()?;
+    if config.circular {
+        return Err(OrfFinderError::InvalidConfig(
+            "variants can not be applied to circular sequences".to_string(),
+        ));
+    }
+    let writer = get_writer(out_format, out_target, config);
+
+    let mut variants_by_seqid: HashMap<&str, Vec<Variant>> = HashMap::new();
+    for variant in variants {
+        variants_by_seqid
+            .entry(variant.seqid.as_str())
+            .or_default()
+            .push(variant.clone());
+    }
+
+    let mut num_records = 0;
+    for record in records {
+        let record = record?;
+        num_records += 1;
+        let record_variants = match variants_by_seqid.remove(record.id.as_str()) {
+            Some(record_variants) => record_variants,
+            None => continue,
+        };
+        // Variants are applied to the bases, packed records are unpacked
+        let record = record.into_unpacked();
+
+        let variant_sequence = VariantSequence::apply(&record.sequence, record_variants)?;
+        let variant_record = SequenceRecord {
+            id: record.id.clone(),
+            sequence: variant_sequence.sequence.clone(),
+            packed: None,
+            offset: None,
+        };
+        let reference_len = record.sequence.len();
+        let reference_orfs = collect_record_orfs(&record, config, postprocessors(&record)?)?;
+        let variant_orfs =
+            collect_record_orfs(&variant_record, config, postprocessors(&variant_record)?)?;
+
+        let (orf_sender, orf_receiver) = crossbeam::channel::unbounded();
+        let strand_mapper = ForwardStrandMapper::new(reference_len);
+        for orf in compare_orfs(
+            reference_orfs,
+            variant_orfs,
+            reference_len,
+            &variant_sequence,
+        ) {
+            // GFF3 positions are located on the forward strand
+            match out_format {
+                OutputType::GFF3 => orf_sender.send(strand_mapper.map(orf))?,
+                _ => orf_sender.send(orf)?,
+            }
+        }
+        drop(orf_sender);
+        writer.write(orf_receiver)?;
+    }
+    writer.finish()?;
+
+    return Ok(num_records);
+}
+
+fn collect_record_orfs(
+    record: &SequenceRecord,
+    config: &FinderConfig,
+    postprocessors: Vec<Box<dyn PostProcessor + Send + Sync>>,
+) -> Result<Vec<ORF>, OrfFinderError> {
+    let search = OrfSearch::new(record.sequence.clone(), config)?.with_seqid(&record.id);
+
+    return OrfIter::new(search, config.threads, postprocessors).collect();
+}
+
+fn search_record(
+    record: SequenceRecord,
+    config: &FinderConfig,
+    postprocessors: &[Box<dyn PostProcessor + Send + Sync>],
+    writer: &(dyn OutWriter + Send + Sync),
+) -> Result<(), OrfFinderError> {
+    let search = match record.packed {
+        Some(packed) => OrfSearch::from_packed(packed, config)?,
+        None => OrfSearch::new(record.sequence, config)?,
+    };
+    let mut search = search.with_seqid(&record.id);
+    if let Some(offset) = record.offset {
+        search = search.with_offset(offset);
+    }
+
+    return run_search(search, config.threads, postprocessors, writer);
+}
+
+/// Finds all ORFs of the sequence and returns them as an iterator instead of writing them.
+/// The search runs in the background while the ORFs are consumed, in an unspecified order.
+/// If the search fails, its error is the last item.
+/// * `sequence` - The sequence on which to search ORFs on
+/// * `config` - The settings of the search, its threads are used in addition to the thread consuming the iterator
+/// * `postprocessors` - Stages applied in order to the found ORFs before they are returned
+pub fn find_orfs_iter(
+    sequence: &str,
+    config: &FinderConfig,
+    postprocessors: Vec<Box<dyn PostProcessor + Send + Sync>>,
+) -> Result<OrfIter, OrfFinderError> {
+    config.validate()?;
+    let search = OrfSearch::new(sequence.to_string(), config)?;
+
+    return Ok(OrfIter::new(search, config.threads, postprocessors));
+}
+
+/// Finds all ORFs of the sequence and collects them, see `find_orfs_iter`.
+/// * `sequence` - The sequence on which to search ORFs on
+/// * `config` - The settings of the search
+/// * `postprocessors` - Stages applied in order to the found ORFs before they are returned
+pub fn collect_orfs(
+    sequence: &str,
+    config: &FinderConfig,
+    postprocessors: Vec<Box<dyn PostProcessor + Send + Sync>>,
+) -> Result<Vec<ORF>, OrfFinderError> {
+    return find_orfs_iter(sequence, config, postprocessors)?.collect();
+}
+
+/// Learns a gene model from the long ORFs of the sequence by running the finder once in advance.
+/// Overlaps between the long ORFs are resolved before training, so that each gene contributes a single start.
+/// * `sequence` - The sequence to train on
+/// * `config` - The settings of the search, only its table, topology, mask, RBS window and threads are used for training
+/// * `training_min_len` - Minimum size of an ORF to be used for training
+pub fn train_gene_model(
+    sequence: String,
+    config: &FinderConfig,
+    training_min_len: usize,
+) -> Result<GeneModel, OrfFinderError> {
+    let training_config = FinderConfig {
+        translational_table: config.translational_table,
+        min_len: training_min_len,
+        circular: config.circular,
+        mask_file: config.mask_file.clone(),
+        masked_areas: config.masked_areas.clone(),
+        // The ribosome binding sites of the training ORFs are learned by the model
+        rbs_window: Some(config.rbs_window.unwrap_or(DEFAULT_RBS_WINDOW)),
+        threads: config.threads,
+        ..FinderConfig::default()
+    };
+    training_config.validate()?;
+
+    let (orf_sender, orf_receiver) = crossbeam::channel::unbounded();
+    let writer = ChannelWriter::new(orf_sender, orf_receiver.clone());
+    let overlap_resolver: Box<dyn PostProcessor + Send + Sync> = Box::new(OverlapResolver::new(
+        sequence.len(),
+        DEFAULT_MAX_SAME_STRAND_OVERLAP,
+        DEFAULT_MAX_OPPOSITE_STRAND_OVERLAP,
+    ));
+
+    let search = OrfSearch::new(sequence.to_string(), &training_config)?;
+    run_search(
+        search,
+        training_config.threads,
+        &[overlap_resolver],
+        &writer,
+    )?;
+
+    let training_orfs: Vec<_> = orf_receiver.try_iter().collect();
+    return Ok(GeneModel::train(
+        &training_orfs,
+        &Alphabet::to_dna(sequence.as_bytes()),
+    ));
 }
@@ -1,102 +1,468 @@
 use std::{
     collections::HashMap,
     fs::{File, OpenOptions},
+    io::Write,
     path::PathBuf,
 };
 
-use clap::Parser;
-use datahandler::filehandler::SequenceFileType;
-use rustyorffinder::{find_orfs, outwriter::outwriter::OutputType};
-
-const MIN_NUM_THREADS: u8 = 4;
-
-pub mod datahandler;
-pub mod finder;
-pub mod models;
-pub mod outwriter;
+use clap::{ArgGroup, Parser};
+use rustyorffinder::{
+    datahandler::{
+        self,
+        filehandler::{SequenceFileType, SequenceRecord},
+        indexed_fasta::IndexedFasta,
+        region::Region,
+        transcript_structure::parse_transcript_structure_file,
+        vcf::{parse_vcf_file, VcfSelection},
+    },
+    error::error::OrfFinderError,
+    find_orfs_in_records, find_spliced_orfs, find_transcript_orfs, find_variant_orfs,
+    finder::{
+        finder_config::FinderConfig, start_selection::StartSelection,
+        strand_selection::StrandSelection,
+    },
+    models::models::{Alphabet, KozakStrength},
+    outwriter::{
+        outwriter::OutputType, spliced_gff_writer::SplicedGffWriter,
+        transcriptome_writer::TranscriptomeWriter,
+    },
+    postprocessor::{
+        orf_classifier::OrfClassifier,
+        overlap_resolver::{
+            OverlapResolver, DEFAULT_MAX_OPPOSITE_STRAND_OVERLAP, DEFAULT_MAX_SAME_STRAND_OVERLAP,
+        },
+        postprocessor::PostProcessor,
+        score_filter::ScoreFilter,
+    },
+    scoring::{
+        coding_model::parse_coding_model,
+        gene_model::{parse_gene_model, write_gene_model, GeneModelScorer},
+        rbs::DEFAULT_RBS_WINDOW,
+        scorer::{get_scorer, ScoringMethod},
+    },
+    train_gene_model,
+};
 
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
+#[clap(group(
+    ArgGroup::new("scorer")
+        .args(&["scoring-method", "gene-model", "train-gene-model", "transcriptome"])
+        .multiple(true)
+))]
 struct Cli {
     /// The path to the sequence file, can be any FD type, including e.g. /dev/stdin
     #[clap(short, long, value_name = "FILE")]
     sequence_path: Option<PathBuf>,
 
-    /// Input kind
-    #[clap(short = 't', long, arg_enum)]
+    /// Input kind, detected from the start of the input by default
+    #[clap(short = 't', long, arg_enum, default_value = "auto")]
     sequence_file_type: SequenceFileType,
 
-    /// Number of threads
-    #[clap(short, long, value_parser = validate_thread_number, default_value_t = 4)]
-    num_threads: u8,
+    /// Memory-map the FASTA sequence file using its index <FILE>.fai instead of reading it
+    #[clap(long, requires = "sequence-path")]
+    indexed: bool,
+
+    /// Only search the region chr:start-end of the indexed FASTA sequence file, may be given multiple times.
+    /// Positions start at 1 and ORFs are reported on the whole sequence
+    #[clap(long, value_name = "REGION", value_parser, requires = "sequence-path")]
+    region: Vec<Region>,
+
+    /// Only search the regions of the BED file within the indexed FASTA sequence file
+    #[clap(long, value_name = "FILE", requires = "sequence-path")]
+    regions_bed: Option<PathBuf>,
+
+    /// Store each sequence with two bits per base, which takes a fraction of the memory but is slower
+    #[clap(long)]
+    packed: bool,
+
+    /// Finder configuration in JSON format, the options given on the command line take precedence
+    #[clap(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+
+    /// Number of threads, defaults to the number of available cores
+    #[clap(short, long, value_parser = validate_thread_number)]
+    num_threads: Option<usize>,
 
     /// Masked regions in GFF3 format
     #[clap(short, long, value_name = "FILE")]
     masked_gff3: Option<PathBuf>,
 
-    /// Minimum size of an ORF
-    #[clap(long, default_value_t = 30)]
-    orf_min_length: usize,
+    /// Annotated CDS of the transcripts in GFF3 or GTF format, classifies the ORFs as uORF, dORF etc. relative to it
+    #[clap(long, value_name = "FILE")]
+    cds_annotation: Option<PathBuf>,
+
+    /// Number of the translational table [default: 11]
+    #[clap(long)]
+    translational_table: Option<u8>,
+
+    /// Treat the sequence as circular, so that ORFs may span its end
+    #[clap(long)]
+    circular: bool,
+
+    /// Minimum size of an ORF [default: 30]
+    #[clap(long)]
+    orf_min_length: Option<usize>,
+
+    /// Maximum size of an ORF, unlimited by default
+    #[clap(long)]
+    orf_max_length: Option<usize>,
 
     /// Output file format
-    #[clap(short = 'h', long, arg_enum)]
-    output_format: OutputType,
+    #[clap(
+        short = 'h',
+        long,
+        arg_enum,
+        required_unless_present_any = &["transcriptome", "spliced-transcripts"]
+    )]
+    output_format: Option<OutputType>,
 
     /// Output file path
     #[clap(short = 'o', long)]
     output_file: Option<PathBuf>,
+
+    /// Transcriptome mode, writes the longest ORFs of each transcript to <PREFIX>.pep, .cds, .gff3 and .bed.
+    /// The strand of each transcript is chosen by the coding score of its ORFs, Fickett if no other scoring is given
+    #[clap(long, value_name = "PREFIX", conflicts_with_all = &["output-format", "output-file"])]
+    transcriptome: Option<PathBuf>,
+
+    /// Maximum number of ORFs reported per transcript in transcriptome mode
+    #[clap(long, default_value_t = 1, requires = "transcriptome")]
+    max_orfs_per_transcript: usize,
+
+    /// Exons of transcripts on the genome input in GTF or GFF3 format. The spliced transcripts are searched
+    /// and their ORFs are written as CDS features on the genome in GFF3 format
+    #[clap(long, value_name = "FILE", conflicts_with_all = &["output-format", "transcriptome"])]
+    spliced_transcripts: Option<PathBuf>,
+
+    /// Variants in VCF format applied to the sequences, only ORFs gained, lost, truncated or extended by them are reported
+    #[clap(long, value_name = "FILE", conflicts_with_all = &["transcriptome", "spliced-transcripts"])]
+    vcf: Option<PathBuf>,
+
+    /// Apply the variants carried by this sample of the VCF file instead of all variants
+    #[clap(long, value_name = "NAME", requires = "vcf")]
+    vcf_sample: Option<String>,
+
+    /// Apply only the variants on this haplotype of the sample, e.g. 1 or 2
+    #[clap(long, requires = "vcf-sample")]
+    haplotype: Option<usize>,
+
+    /// Search a ribosome binding site within this number of bases upstream of each start codon
+    #[clap(long, value_name = "BASES")]
+    rbs_window: Option<usize>,
+
+    /// Start codons to report for ORFs sharing a stop codon [default: all]
+    #[clap(long, arg_enum)]
+    start_selection: Option<StartSelection>,
+
+    /// Strands to search, e.g. only forward for stranded transcripts [default: both]
+    #[clap(long, arg_enum)]
+    strand: Option<StrandSelection>,
+
+    /// Comma separated frames 1 to 3 to search on each strand [default: 1,2,3]
+    #[clap(long, value_delimiter = ',')]
+    frames: Option<Vec<u8>>,
+
+    /// Score the Kozak context of each start codon
+    #[clap(long)]
+    kozak: bool,
+
+    /// Minimum Kozak strength of a start codon, implies Kozak scoring
+    #[clap(long, arg_enum)]
+    min_kozak_strength: Option<KozakStrength>,
+
+    /// Report the ORFs of RNA input with U instead of T
+    #[clap(long)]
+    preserve_alphabet: bool,
+
+    /// Resolve overlapping ORFs across frames and strands, keeping the longest ORFs
+    #[clap(long)]
+    resolve_overlaps: bool,
+
+    /// Maximum overlap in bases between two ORFs on the same strand when resolving overlaps
+    #[clap(long, default_value_t = DEFAULT_MAX_SAME_STRAND_OVERLAP)]
+    max_overlap_same_strand: usize,
+
+    /// Maximum overlap in bases between two ORFs on opposite strands when resolving overlaps
+    #[clap(long, default_value_t = DEFAULT_MAX_OPPOSITE_STRAND_OVERLAP)]
+    max_overlap_opposite_strand: usize,
+
+    /// Method used to score the coding potential of each ORF
+    #[clap(long, arg_enum)]
+    scoring_method: Option<ScoringMethod>,
+
+    /// Coding model in JSON format, required by the codon usage and hexamer scoring methods
+    #[clap(long, value_name = "FILE")]
+    coding_model: Option<PathBuf>,
+
+    /// Minimum coding score of an ORF, requires a scoring method or gene model
+    #[clap(long, requires = "scorer")]
+    min_score: Option<f64>,
+
+    /// Train a gene model on the long ORFs of the first input record and rescore all ORFs with it
+    #[clap(long, conflicts_with_all = &["scoring-method", "gene-model"])]
+    train_gene_model: bool,
+
+    /// Minimum size of an ORF to be used for training the gene model
+    #[clap(long, default_value_t = 300)]
+    training_min_length: usize,
+
+    /// Path to save the trained gene model to in JSON format
+    #[clap(long, value_name = "FILE", requires = "train-gene-model")]
+    save_gene_model: Option<PathBuf>,
+
+    /// Previously trained gene model in JSON format used to score all ORFs
+    #[clap(long, value_name = "FILE", conflicts_with = "scoring-method")]
+    gene_model: Option<PathBuf>,
 }
 
 fn main() {
     env_logger::init();
 
     let cli = Cli::parse();
-    let input_io: Box<dyn std::io::Read + Send + Sync> = match cli.sequence_path {
-        Some(path) => {
-            let file: Box<dyn std::io::Read + Send + Sync> =
-                Box::new(OpenOptions::new().read(true).open(path).unwrap());
-            file
+    if let Err(err) = run(cli) {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+fn run(cli: Cli) -> Result<(), OrfFinderError> {
+    let mut config = match &cli.config {
+        Some(path) => FinderConfig::from_file(path.clone())?,
+        None => FinderConfig::default(),
+    };
+    if let Some(num_threads) = cli.num_threads {
+        config.threads = num_threads;
+    }
+    if let Some(translational_table) = cli.translational_table {
+        config.translational_table = translational_table;
+    }
+    if let Some(orf_min_length) = cli.orf_min_length {
+        config.min_len = orf_min_length;
+    }
+    if let Some(orf_max_length) = cli.orf_max_length {
+        config.max_len = Some(orf_max_length);
+    }
+    if let Some(rbs_window) = cli.rbs_window {
+        config.rbs_window = Some(rbs_window);
+    }
+    // Gene models score the ribosome binding site of each start
+    if (cli.train_gene_model || cli.gene_model.is_some()) && config.rbs_window.is_none() {
+        config.rbs_window = Some(DEFAULT_RBS_WINDOW);
+    }
+    if let Some(start_selection) = cli.start_selection {
+        config.start_selection = start_selection;
+    }
+    if let Some(strand) = cli.strand {
+        config.strand = strand;
+    }
+    if let Some(frames) = cli.frames {
+        config.frames = frames;
+    }
+    if let Some(min_kozak_strength) = cli.min_kozak_strength {
+        config.min_kozak_strength = Some(min_kozak_strength);
+    }
+    config.circular |= cli.circular;
+    config.kozak_scoring |= cli.kozak;
+    config.preserve_alphabet |= cli.preserve_alphabet;
+    config.packed |= cli.packed;
+
+    let mut regions = cli.region;
+    if let Some(path) = cli.regions_bed {
+        regions.extend(datahandler::region::parse_bed_file(path)?);
+    }
+    if !regions.is_empty() && (cli.spliced_transcripts.is_some() || cli.vcf.is_some()) {
+        return Err(OrfFinderError::InvalidConfig(
+            "transcripts can only be spliced from and variants applied to whole sequences"
+                .to_string(),
+        ));
+    }
+    if !regions.is_empty() && config.circular {
+        return Err(OrfFinderError::InvalidConfig(
+            "regions can not be searched as circular sequences".to_string(),
+        ));
+    }
+
+    let records = match (cli.sequence_path, cli.indexed) {
+        (Some(path), _) if !regions.is_empty() => {
+            IndexedFasta::open(&path)?.into_region_records(regions, config.packed)?
+        }
+        (Some(path), true) => IndexedFasta::open(&path)?.into_records(config.packed),
+        (Some(path), false) => {
+            let file = OpenOptions::new().read(true).open(&path).map_err(|err| {
+                OrfFinderError::InputParse(format!("{}: {}", path.display(), err))
+            })?;
+            datahandler::filehandler::read_records(Box::new(file), cli.sequence_file_type)
         }
-        None => Box::new(std::io::stdin()),
+        (None, _) => datahandler::filehandler::read_records(
+            Box::new(std::io::stdin()),
+            cli.sequence_file_type,
+        ),
     };
 
-    let sequences = datahandler::filehandler::read_input(input_io, cli.sequence_file_type).unwrap();
+    if let Some(value) = cli.masked_gff3 {
+        config.mask_file = Some(value);
+        config.read_mask_file()?;
+    }
+    config.validate()?;
 
-    let masked_areas = match cli.masked_gff3 {
-        Some(value) => datahandler::mask_file::parse_mask_gff3_file(value).unwrap(),
+    let vcf_selection = match (cli.vcf_sample, cli.haplotype) {
+        (None, _) => VcfSelection::AllVariants,
+        (Some(sample), None) => VcfSelection::Sample(sample),
+        (Some(_), Some(0)) => {
+            return Err(OrfFinderError::InvalidConfig(
+                "haplotypes are numbered from 1".to_string(),
+            ))
+        }
+        (Some(sample), Some(haplotype)) => VcfSelection::Haplotype(sample, haplotype - 1),
+    };
+    let variants = cli
+        .vcf
+        .map(|path| parse_vcf_file(path, &vcf_selection))
+        .transpose()?;
+    let cds_annotation = match cli.cds_annotation {
+        Some(path) => datahandler::cds_annotation::parse_cds_annotation_file(path)?,
         None => HashMap::new(),
     };
+    let coding_model = cli.coding_model.map(parse_coding_model).transpose()?;
+    // A gene model is trained on the first record and used for all following records
+    let mut gene_model = cli.gene_model.map(parse_gene_model).transpose()?;
+
+    // Transcriptome mode selects the strand of each transcript by the coding potential of its ORFs
+    let scoring_method = match (cli.scoring_method, &cli.transcriptome) {
+        (None, Some(_)) if !cli.train_gene_model && gene_model.is_none() => {
+            Some(ScoringMethod::Fickett)
+        }
+        (scoring_method, _) => scoring_method,
+    };
+
+    let postprocessors = |record: &SequenceRecord| {
+        let mut postprocessors: Vec<Box<dyn PostProcessor + Send + Sync>> = Vec::new();
+        // Packed records are only unpacked for scoring
+        if scoring_method.is_some() || cli.train_gene_model || gene_model.is_some() {
+            // RNA is scored as DNA, just like it is searched
+            let bases = record.bases();
+            let sequence = Alphabet::to_dna(&bases);
+            if let Some(scoring_method) = scoring_method {
+                let scorer = get_scorer(scoring_method, coding_model.clone(), &sequence)?;
+                postprocessors.push(Box::new(ScoreFilter::new(scorer, cli.min_score)));
+            }
+
+            if cli.train_gene_model && gene_model.is_none() {
+                let trained_model = train_gene_model(
+                    String::from_utf8_lossy(&bases).into_owned(),
+                    &config,
+                    cli.training_min_length,
+                )?;
+                if let Some(path) = &cli.save_gene_model {
+                    write_gene_model(&trained_model, path.clone())?;
+                }
+                gene_model = Some(trained_model);
+            }
+            if let Some(gene_model) = &gene_model {
+                let scorer = Box::new(GeneModelScorer::new(gene_model, &sequence));
+                postprocessors.push(Box::new(ScoreFilter::new(scorer, cli.min_score)));
+            }
+        }
 
-    let output_type = cli.output_format;
+        // Reverse positions of regions are located on the whole sequence
+        let source_len = record
+            .offset
+            .map_or(record.len(), |offset| offset.source_len);
+        if cli.resolve_overlaps {
+            postprocessors.push(Box::new(OverlapResolver::new(
+                source_len,
+                cli.max_overlap_same_strand,
+                cli.max_overlap_opposite_strand,
+            )));
+        }
 
-    let out_io: Box<dyn std::io::Write + Send + Sync> = match cli.output_file {
-        Some(path) => Box::new(File::create(path).unwrap()),
-        None => Box::new(std::io::stdout()),
+        if let Some(cds) = cds_annotation.get(&record.id) {
+            if cds.end > source_len {
+                return Err(OrfFinderError::AnnotationParse(format!(
+                    "the CDS of {} exceeds its sequence of {} bases",
+                    record.id, source_len
+                )));
+            }
+            postprocessors.push(Box::new(OrfClassifier::new(*cds, source_len)));
+        }
+
+        return Ok(postprocessors);
     };
 
-    let sequence = sequences.get(0).unwrap();
-    find_orfs(
-        sequence.to_string(),
-        masked_areas,
-        cli.num_threads,
-        false,
-        11,
-        cli.orf_min_length,
-        output_type,
-        out_io,
-    );
+    let num_records = match (
+        cli.transcriptome,
+        cli.spliced_transcripts,
+        cli.output_format,
+    ) {
+        (Some(prefix), _, _) => {
+            let outputs = TranscriptomeWriter::create(&prefix)?;
+            find_transcript_orfs(
+                records,
+                &config,
+                cli.max_orfs_per_transcript,
+                &outputs,
+                postprocessors,
+            )?
+        }
+        (None, Some(path), _) => {
+            let transcripts = parse_transcript_structure_file(path)?;
+            let outputs = SplicedGffWriter::new(open_output(cli.output_file)?).with_config(&config);
+            find_spliced_orfs(records, &transcripts, &config, &outputs, postprocessors)?
+        }
+        (None, None, Some(output_type)) => {
+            let out_io = open_output(cli.output_file)?;
+            match &variants {
+                Some(variants) => find_variant_orfs(
+                    records,
+                    variants,
+                    &config,
+                    output_type,
+                    out_io,
+                    postprocessors,
+                )?,
+                None => {
+                    find_orfs_in_records(records, &config, output_type, out_io, postprocessors)?
+                }
+            }
+        }
+        (None, None, None) => {
+            unreachable!("clap requires an output format without transcriptome or spliced mode")
+        }
+    };
+    if num_records == 0 {
+        return Err(OrfFinderError::InputParse(
+            "the input contains no sequence".to_string(),
+        ));
+    }
+
+    return Ok(());
+}
+
+/// Creates the output file or writes to stdout if no path is given.
+fn open_output(path: Option<PathBuf>) -> Result<Box<dyn Write + Send + Sync>, OrfFinderError> {
+    return match path {
+        Some(path) => {
+            let file = File::create(&path).map_err(|err| {
+                let message = format!("{}: {}", path.display(), err);
+                OrfFinderError::OutputIo(std::io::Error::new(err.kind(), message))
+            })?;
+            Ok(Box::new(file))
+        }
+        None => Ok(Box::new(std::io::stdout())),
+    };
 }
 
-fn validate_thread_number(num_threads_string: &str) -> Result<u8, String> {
-    let num_threads: u8 = num_threads_string
+fn validate_thread_number(num_threads_string: &str) -> Result<usize, String> {
+    let num_threads: usize = num_threads_string
         .parse()
         .map_err(|_| format!("`{}` isn't a valid number of threads", num_threads_string))?;
 
-    if num_threads < MIN_NUM_THREADS {
-        return Err(format!(
-            "Insufficient number of threads provided: At least {} threads required to run.",
-            MIN_NUM_THREADS
-        ));
+    if num_threads == 0 {
+        return Err(
+            "Insufficient number of threads provided: At least 1 thread required to run."
+                .to_string(),
+        );
     }
 
     return Ok(num_threads);
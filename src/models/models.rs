@@ -1,11 +1,120 @@
-use serde::{Deserialize, Serialize};
+use std::{borrow::Cow, fmt, sync::Arc};
+
+use clap::ValueEnum;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ORF {
+    /// Identifier of the sequence record the ORF was found on
+    pub seqid: Arc<str>,
     pub start_position: usize,
     pub stop_position: usize,
-    pub sequence: String,
+    pub sequence: SequenceSlice,
     pub direction: Direction,
+    /// Coding potential of the ORF, only set if a scoring method was selected
+    pub score: Option<f64>,
+    /// Ribosome binding site upstream of the start codon, only set if RBS detection is enabled
+    pub rbs: Option<RbsSite>,
+    /// Kozak context of the start codon, only set if Kozak scoring is enabled
+    pub kozak: Option<KozakContext>,
+    /// Location relative to the annotated CDS of the transcript, only set if a CDS annotation is given
+    pub orf_class: Option<OrfClass>,
+    /// Change relative to the ORFs of the reference, only set if variants are applied
+    pub variant_effect: Option<VariantEffect>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RbsSite {
+    pub motif: String,
+    /// Number of bases between the motif and the start codon
+    pub spacer: usize,
+    pub score: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KozakContext {
+    /// Bases -6 to +4 relative to the first base of the start codon
+    pub context: String,
+    pub score: f64,
+    pub strength: KozakStrength,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Serialize, Deserialize, Debug)]
+pub enum KozakStrength {
+    /// Neither a purine at -3 nor a G at +4
+    Weak,
+    /// Either a purine at -3 or a G at +4
+    Adequate,
+    /// A purine at -3 and a G at +4
+    Strong,
+}
+
+/// Location of an ORF relative to the annotated CDS on the same strand of a transcript.
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum OrfClass {
+    /// The annotated CDS itself
+    AnnotatedCds,
+    /// Starts and ends upstream of the CDS
+    Uorf,
+    /// Starts upstream of the CDS and ends within it in another frame
+    OverlappingUorf,
+    /// Starts upstream of the CDS in its frame, the CDS with an extended N-terminus
+    NTerminalExtension,
+    /// Starts within the CDS in its frame, the CDS with a truncated N-terminus
+    NTerminalTruncation,
+    /// Starts within the CDS in another frame
+    InternalOutOfFrame,
+    /// Starts downstream of the CDS
+    Dorf,
+}
+
+/// Change of an ORF caused by the variants applied to the reference sequence.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct VariantEffect {
+    pub kind: VariantEffectKind,
+    /// Identifiers of the variants within the ORF of the reference or the variant sequence
+    pub variant_ids: Vec<String>,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum VariantEffectKind {
+    /// Only found on the variant sequence
+    Gained,
+    /// Only found on the reference sequence
+    Lost,
+    /// Starts at the same position but stops earlier on the variant sequence
+    Truncated,
+    /// Starts at the same position but stops later on the variant sequence
+    Extended,
+}
+
+impl fmt::Display for VariantEffectKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            VariantEffectKind::Gained => "gained",
+            VariantEffectKind::Lost => "lost",
+            VariantEffectKind::Truncated => "truncated",
+            VariantEffectKind::Extended => "extended",
+        };
+        return write!(f, "{}", name);
+    }
+}
+
+impl fmt::Display for OrfClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            OrfClass::AnnotatedCds => "annotated_cds",
+            OrfClass::Uorf => "uorf",
+            OrfClass::OverlappingUorf => "overlapping_uorf",
+            OrfClass::NTerminalExtension => "n_terminal_extension",
+            OrfClass::NTerminalTruncation => "n_terminal_truncation",
+            OrfClass::InternalOutOfFrame => "internal_out_of_frame",
+            OrfClass::Dorf => "dorf",
+        };
+        return write!(f, "{}", name);
+    }
 }
 
 pub struct ORFPositions {
@@ -14,8 +123,143 @@ pub struct ORFPositions {
     pub strand: Direction,
 }
 
-#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
 pub enum Direction {
     FORWARD,
     REVERSE,
 }
+
+/// Nucleotide alphabet of a sequence, RNA uses U in place of T.
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum Alphabet {
+    Dna,
+    Rna,
+}
+
+impl Alphabet {
+    /// Converts a sequence to uppercase DNA in place and returns the alphabet of the input.
+    /// Sequences containing any U are considered RNA, soft-masked lowercase bases are unmasked.
+    pub fn normalize(sequence: &mut [u8]) -> Alphabet {
+        let mut alphabet = Alphabet::Dna;
+        for base in sequence.iter_mut() {
+            base.make_ascii_uppercase();
+            if *base == b'U' {
+                *base = b'T';
+                alphabet = Alphabet::Rna;
+            }
+        }
+
+        return alphabet;
+    }
+
+    /// The DNA representation of the sequence, only copied if it contains a U.
+    pub fn to_dna(sequence: &[u8]) -> Cow<'_, [u8]> {
+        if !sequence.contains(&b'U') {
+            return Cow::Borrowed(sequence);
+        }
+
+        let mut dna = sequence.to_vec();
+        Alphabet::normalize(&mut dna);
+        return Cow::Owned(dna);
+    }
+}
+
+/// A part of a sequence shared by all ORFs found on it.
+/// Cloning only copies the reference to the shared buffer and the coordinates, not the bases.
+/// The bases are stored as DNA, RNA slices are only displayed with U.
+#[derive(Clone)]
+pub struct SequenceSlice {
+    buffer: Arc<[u8]>,
+    start: usize,
+    end: usize,
+    alphabet: Alphabet,
+}
+
+impl SequenceSlice {
+    pub fn new(buffer: Arc<[u8]>, start: usize, end: usize) -> Self {
+        return SequenceSlice {
+            buffer: buffer,
+            start: start,
+            end: end,
+            alphabet: Alphabet::Dna,
+        };
+    }
+
+    /// Sets the alphabet used to display the slice.
+    pub fn with_alphabet(mut self, alphabet: Alphabet) -> Self {
+        self.alphabet = alphabet;
+        return self;
+    }
+
+    /// The bases of the slice as DNA, regardless of its alphabet.
+    pub fn as_bytes(&self) -> &[u8] {
+        return &self.buffer[self.start..self.end];
+    }
+
+    pub fn alphabet(&self) -> Alphabet {
+        return self.alphabet;
+    }
+
+    /// The bases of the slice in its alphabet.
+    fn display_bytes(&self) -> Cow<'_, [u8]> {
+        return match self.alphabet {
+            Alphabet::Dna => Cow::Borrowed(self.as_bytes()),
+            Alphabet::Rna => Cow::Owned(
+                self.as_bytes()
+                    .iter()
+                    .map(|base| match base {
+                        b'T' => b'U',
+                        base => *base,
+                    })
+                    .collect(),
+            ),
+        };
+    }
+
+    pub fn len(&self) -> usize {
+        return self.end - self.start;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.start == self.end;
+    }
+}
+
+impl From<&str> for SequenceSlice {
+    fn from(sequence: &str) -> Self {
+        let mut bases = sequence.as_bytes().to_vec();
+        let alphabet = Alphabet::normalize(&mut bases);
+        return SequenceSlice::new(Arc::from(bases), 0, sequence.len()).with_alphabet(alphabet);
+    }
+}
+
+impl From<String> for SequenceSlice {
+    fn from(sequence: String) -> Self {
+        return SequenceSlice::from(sequence.as_str());
+    }
+}
+
+impl fmt::Display for SequenceSlice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return f.write_str(&String::from_utf8_lossy(&self.display_bytes()));
+    }
+}
+
+impl fmt::Debug for SequenceSlice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "\"{}\"", self);
+    }
+}
+
+impl Serialize for SequenceSlice {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        return serializer.serialize_str(&String::from_utf8_lossy(&self.display_bytes()));
+    }
+}
+
+impl<'de> Deserialize<'de> for SequenceSlice {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let sequence = String::deserialize(deserializer)?;
+        return Ok(SequenceSlice::from(sequence));
+    }
+}
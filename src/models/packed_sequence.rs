@@ -0,0 +1,155 @@
+use crate::error::error::OrfFinderError;
+
+/// Bases in the order of their two bit codes, complementary bases have complementary codes
+const PACKED_BASES: [u8; 4] = [b'A', b'C', b'G', b'T'];
+const BASES_PER_BYTE: usize = 4;
+
+/// A DNA sequence stored with two bits per base, a quarter of the memory of one byte per base.
+/// A, C, G and T are packed, unknown bases (N) are stored as runs next to the packed bases.
+pub struct PackedSequence {
+    packed: Vec<u8>,
+    len: usize,
+    /// Start and end of each run of unknown bases in ascending order, their bases are packed as A
+    unknown: Vec<(usize, usize)>,
+}
+
+impl PackedSequence {
+    /// * `capacity` - Expected number of bases
+    pub fn with_capacity(capacity: usize) -> Self {
+        return PackedSequence {
+            packed: Vec::with_capacity(capacity.div_ceil(BASES_PER_BYTE)),
+            len: 0,
+            unknown: Vec::new(),
+        };
+    }
+
+    /// Packs the bases, fails on the first base that is not A, C, G, T or N.
+    pub fn from_bases(bases: &[u8]) -> Result<Self, OrfFinderError> {
+        let mut sequence = PackedSequence::with_capacity(bases.len());
+        for base in bases {
+            sequence.push(*base)?;
+        }
+
+        return Ok(sequence);
+    }
+
+    /// Appends a base, fails if it is not A, C, G, T or N.
+    pub fn push(&mut self, base: u8) -> Result<(), OrfFinderError> {
+        let code = match base {
+            b'A' => 0,
+            b'C' => 1,
+            b'G' => 2,
+            b'T' => 3,
+            b'N' => {
+                match self.unknown.last_mut() {
+                    Some((_, end)) if *end == self.len => *end += 1,
+                    _ => self.unknown.push((self.len, self.len + 1)),
+                }
+                0
+            }
+            _ => {
+                return Err(OrfFinderError::InvalidBase {
+                    base: base,
+                    position: self.len,
+                })
+            }
+        };
+
+        let shift = 2 * (self.len % BASES_PER_BYTE);
+        match shift {
+            0 => self.packed.push(code),
+            _ => *self.packed.last_mut().unwrap() |= code << shift,
+        }
+        self.len += 1;
+
+        return Ok(());
+    }
+
+    pub fn len(&self) -> usize {
+        return self.len;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.len == 0;
+    }
+
+    /// The base at the position, panics if the position exceeds the sequence.
+    #[inline(always)]
+    pub fn base(&self, position: usize) -> u8 {
+        let code = self.code(position);
+        if self.is_unknown(position) {
+            return b'N';
+        }
+
+        return PACKED_BASES[code as usize];
+    }
+
+    /// The complement of the base at the position, panics if the position exceeds the sequence.
+    #[inline(always)]
+    pub fn complement(&self, position: usize) -> u8 {
+        let code = self.code(position);
+        if self.is_unknown(position) {
+            return b'N';
+        }
+
+        return PACKED_BASES[3 - code as usize];
+    }
+
+    #[inline(always)]
+    fn is_unknown(&self, position: usize) -> bool {
+        if self.unknown.is_empty() {
+            return false;
+        }
+
+        let index = self.unknown.partition_point(|(_, end)| *end <= position);
+        return self
+            .unknown
+            .get(index)
+            .is_some_and(|(start, _)| *start <= position);
+    }
+
+    #[inline(always)]
+    fn code(&self, position: usize) -> u8 {
+        assert!(
+            position < self.len,
+            "position {} exceeds the sequence",
+            position
+        );
+        let shift = 2 * (position % BASES_PER_BYTE);
+        return (self.packed[position / BASES_PER_BYTE] >> shift) & 0b11;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PackedSequence;
+
+    #[test]
+    fn packs_and_complements_bases() {
+        let bases = b"ACGTTGCAA";
+        let sequence = PackedSequence::from_bases(bases).unwrap();
+
+        assert_eq!(sequence.len(), bases.len());
+        let unpacked: Vec<u8> = (0..sequence.len()).map(|i| sequence.base(i)).collect();
+        assert_eq!(unpacked, bases);
+        let complement: Vec<u8> = (0..sequence.len())
+            .map(|i| sequence.complement(i))
+            .collect();
+        assert_eq!(complement, b"TGCAACGTT");
+
+        assert!(PackedSequence::from_bases(b"ACGX").is_err());
+    }
+
+    #[test]
+    fn stores_unknown_bases() {
+        let bases = b"NACNNGTN";
+        let sequence = PackedSequence::from_bases(bases).unwrap();
+
+        let unpacked: Vec<u8> = (0..sequence.len()).map(|i| sequence.base(i)).collect();
+        assert_eq!(unpacked, bases);
+        let complement: Vec<u8> = (0..sequence.len())
+            .map(|i| sequence.complement(i))
+            .collect();
+        assert_eq!(complement, b"NTGNNCAN");
+    }
+}
@@ -1,35 +1,41 @@
-use std::{
-    cell::RefCell,
-    sync::{Mutex, RwLock},
-    thread::{self, Thread},
-};
-
 use crossbeam::channel::{Receiver, Sender};
 
-use crate::models::models::ORF;
+use crate::{error::error::OrfFinderError, models::models::ORF};
 
 use super::outwriter::OutWriter;
 
+/// Forwards the ORFs into a channel, e.g. to consume them in-process.
+/// Once the receiving side is dropped the remaining ORFs are discarded so the pipeline can finish.
 pub struct ChannelWriter {
     send: Sender<ORF>,
-    recv: Receiver<ORF>,
+    /// Keeps the channel connected while the writer exists, none if the consumer may stop early
+    _recv: Option<Receiver<ORF>>,
 }
 
 impl ChannelWriter {
     pub fn new(send: Sender<ORF>, recv: Receiver<ORF>) -> Self {
         return ChannelWriter {
             send: send,
-            recv: recv,
+            _recv: Some(recv),
+        };
+    }
+
+    /// A writer that does not hold a receiver, so that dropping the consumer's receiver disconnects the channel.
+    pub fn detached(send: Sender<ORF>) -> Self {
+        return ChannelWriter {
+            send: send,
+            _recv: None,
         };
     }
 }
 
 impl OutWriter for ChannelWriter {
-    fn write(&self, orfs: Receiver<ORF>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    fn write(&self, orfs: Receiver<ORF>) -> Result<(), OrfFinderError> {
         for orf in orfs {
-            self.send.send(orf).unwrap();
+            // The consumer may stop early, the stages before still have to be drained
+            let _ = self.send.send(orf);
         }
 
-        Ok(())
+        return Ok(());
     }
 }
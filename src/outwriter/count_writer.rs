@@ -1,18 +1,88 @@
+use std::{
+    io,
+    sync::atomic::{AtomicU64, Ordering},
+    sync::Mutex,
+};
+
 use super::outwriter::OutWriter;
+use crate::error::error::OrfFinderError;
+
+/// Counts the ORFs of all sequences and writes their total once all sequences are searched.
+pub struct CountWriter<T: io::Write> {
+    writer: Mutex<T>,
+    count: AtomicU64,
+}
 
-pub struct CountWriter {}
+impl<T: io::Write> CountWriter<T> {
+    pub fn new(writer: T) -> Self {
+        return CountWriter {
+            writer: Mutex::new(writer),
+            count: AtomicU64::new(0),
+        };
+    }
+}
 
-impl OutWriter for CountWriter {
+impl<T: io::Write> OutWriter for CountWriter<T> {
     fn write(
         &self,
         orfs: crossbeam::channel::Receiver<crate::models::models::ORF>,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<(), OrfFinderError> {
         let mut i: u64 = 0;
         for _ in orfs {
-            i = i + 1;
+            i += 1;
         }
 
-        println!("Count: {}", i);
+        self.count.fetch_add(i, Ordering::Relaxed);
+        return Ok(());
+    }
+
+    fn finish(&self) -> Result<(), OrfFinderError> {
+        let mut writer = self.writer.lock().unwrap();
+        writeln!(writer, "Count: {}", self.count.load(Ordering::Relaxed))
+            .map_err(OrfFinderError::OutputIo)?;
+        writer.flush().map_err(OrfFinderError::OutputIo)?;
         return Ok(());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        models::models::{Direction, SequenceSlice, ORF},
+        outwriter::outwriter::OutWriter,
+    };
+
+    use super::CountWriter;
+
+    #[test]
+    fn writes_total_of_all_sequences() {
+        let count_writer = CountWriter::new(Vec::new());
+        for count in [3, 0, 2] {
+            let (orf_sender, orf_receiver) = crossbeam::channel::unbounded();
+            for _ in 0..count {
+                orf_sender
+                    .send(ORF {
+                        seqid: Arc::from("sequence"),
+                        start_position: 0,
+                        stop_position: 0,
+                        sequence: SequenceSlice::from(""),
+                        direction: Direction::FORWARD,
+                        score: None,
+                        rbs: None,
+                        kozak: None,
+                        orf_class: None,
+                        variant_effect: None,
+                    })
+                    .unwrap();
+            }
+            drop(orf_sender);
+            count_writer.write(orf_receiver).unwrap();
+        }
+        count_writer.finish().unwrap();
+
+        let output = count_writer.writer.into_inner().unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "Count: 5\n");
+    }
+}
@@ -1,38 +1,106 @@
-use std::{io, sync::Mutex};
+use std::{
+    io,
+    sync::atomic::{AtomicBool, Ordering},
+    sync::Mutex,
+};
 
 use super::outwriter::OutWriter;
+use crate::{
+    error::error::OrfFinderError, finder::finder_config::FinderConfig, models::models::ORF,
+};
 use bio::io::gff;
 
 pub struct GffWriter<T: io::Write> {
-    writer: Mutex<gff::Writer<T>>,
+    writer: Mutex<T>,
+    /// Configuration of the search in JSON format, written as a directive after the version
+    config_json: Option<String>,
+    /// The header is written once, before the ORFs of the first sequence
+    header_written: AtomicBool,
 }
 
 impl<T: io::Write> GffWriter<T> {
     pub fn new(writer: T) -> Self {
-        let gff_writer = gff::Writer::new(writer, gff::GffType::GFF3);
         return GffWriter {
-            writer: Mutex::new(gff_writer),
+            writer: Mutex::new(writer),
+            config_json: None,
+            header_written: AtomicBool::new(false),
         };
     }
+
+    /// Records the configuration of the search in the header.
+    pub fn with_config(mut self, config: &FinderConfig) -> Self {
+        self.config_json = Some(config.to_json());
+        return self;
+    }
+}
+
+/// Adds the score and the optional details of the ORF, e.g. its RBS, to the GFF record.
+pub(crate) fn insert_orf_details(gff_record: &mut gff::Record, orf: &ORF) {
+    if let Some(score) = orf.score {
+        *gff_record.score_mut() = format!("{:.4}", score);
+    }
+
+    let attributes = gff_record.attributes_mut();
+    if let Some(rbs) = &orf.rbs {
+        attributes.insert("rbs_motif".to_string(), rbs.motif.clone());
+        attributes.insert("rbs_spacer".to_string(), rbs.spacer.to_string());
+        attributes.insert("rbs_score".to_string(), format!("{:.1}", rbs.score));
+    }
+    if let Some(kozak) = &orf.kozak {
+        attributes.insert("kozak_context".to_string(), kozak.context.clone());
+        attributes.insert("kozak_score".to_string(), format!("{:.1}", kozak.score));
+        attributes.insert(
+            "kozak_strength".to_string(),
+            format!("{:?}", kozak.strength).to_lowercase(),
+        );
+    }
+    if let Some(orf_class) = orf.orf_class {
+        attributes.insert("orf_class".to_string(), orf_class.to_string());
+    }
+    if let Some(variant_effect) = &orf.variant_effect {
+        attributes.insert(
+            "variant_effect".to_string(),
+            variant_effect.kind.to_string(),
+        );
+        if !variant_effect.variant_ids.is_empty() {
+            attributes.insert(
+                "variant_ids".to_string(),
+                variant_effect.variant_ids.join(","),
+            );
+        }
+    }
 }
 
 impl<T: io::Write> OutWriter for GffWriter<T> {
     fn write(
         &self,
         orfs: crossbeam::channel::Receiver<crate::models::models::ORF>,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let mut gff_writer = self.writer.lock().unwrap();
+    ) -> Result<(), OrfFinderError> {
+        let mut writer = self.writer.lock().unwrap();
+        if !self.header_written.swap(true, Ordering::Relaxed) {
+            writeln!(writer, "##gff-version 3").map_err(OrfFinderError::OutputIo)?;
+            if let Some(config_json) = &self.config_json {
+                writeln!(writer, "#!rustyorffinder-config {}", config_json)
+                    .map_err(OrfFinderError::OutputIo)?;
+            }
+        }
+
+        let mut gff_writer = gff::Writer::new(&mut *writer, gff::GffType::GFF3);
 
         for orf in orfs {
             let orf_uuid = uuid::Uuid::new_v4();
 
             let mut gff_record = gff::Record::new();
+            // GFF3 positions start at 1 and the end is inclusive
             let start = gff_record.start_mut();
-            *start = orf.start_position as u64;
+            *start = orf.start_position as u64 + 1;
             let stop = gff_record.end_mut();
             *stop = orf.stop_position as u64;
             let name = gff_record.seqname_mut();
-            *name = orf_uuid.to_string();
+            *name = orf.seqid.to_string();
+            gff_record
+                .attributes_mut()
+                .insert("ID".to_string(), orf_uuid.to_string());
             let strand = gff_record.strand_mut();
             *strand = match orf.direction {
                 crate::models::models::Direction::FORWARD => "+".to_string(),
@@ -40,10 +108,64 @@ impl<T: io::Write> OutWriter for GffWriter<T> {
             };
             let feature_type = gff_record.feature_type_mut();
             *feature_type = "ORF".to_string();
+            insert_orf_details(&mut gff_record, &orf);
 
-            gff_writer.write(&gff_record)?;
+            gff_writer
+                .write(&gff_record)
+                .map_err(|err| OrfFinderError::OutputIo(io::Error::other(err)))?;
         }
 
+        // The GFF writer buffers the records until it is dropped
+        drop(gff_writer);
+        writer.flush().map_err(OrfFinderError::OutputIo)?;
         return Ok(());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        models::models::{Direction, SequenceSlice, ORF},
+        outwriter::outwriter::OutWriter,
+        test_utils::SharedBuffer,
+    };
+
+    use super::GffWriter;
+
+    #[test]
+    fn writes_one_based_inclusive_positions() {
+        // ATG AAA CCC followed by its stop codon TAG
+        let orf = ORF {
+            seqid: Arc::from("chr1"),
+            start_position: 0,
+            stop_position: 9,
+            sequence: SequenceSlice::from("ATGAAACCC"),
+            direction: Direction::FORWARD,
+            score: None,
+            rbs: None,
+            kozak: None,
+            orf_class: None,
+            variant_effect: None,
+        };
+        let out_target = SharedBuffer::default();
+        let (orf_sender, orf_receiver) = crossbeam::channel::unbounded();
+        orf_sender.send(orf).unwrap();
+        drop(orf_sender);
+
+        GffWriter::new(out_target.clone())
+            .write(orf_receiver)
+            .unwrap();
+
+        let gff = out_target.contents();
+        let columns: Vec<&str> = gff
+            .lines()
+            .find(|line| !line.starts_with('#'))
+            .unwrap()
+            .split('\t')
+            .collect();
+        assert_eq!(columns[0], "chr1");
+        assert_eq!((columns[3], columns[4], columns[6]), ("1", "9", "+"));
+    }
+}
@@ -2,3 +2,6 @@ pub mod channel_writer;
 pub mod count_writer;
 pub mod gff_writer;
 pub mod outwriter;
+pub mod spliced_gff_writer;
+pub mod transcriptome_writer;
+pub mod tsv_writer;
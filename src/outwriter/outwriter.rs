@@ -2,28 +2,39 @@ use std::io::Write;
 
 use crossbeam::channel::Receiver;
 
-use crate::models::models::ORF;
+use crate::{
+    error::error::OrfFinderError, finder::finder_config::FinderConfig, models::models::ORF,
+};
 use clap::ValueEnum;
 
-use super::{count_writer::CountWriter, gff_writer::GffWriter};
+use super::{count_writer::CountWriter, gff_writer::GffWriter, tsv_writer::TsvWriter};
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 pub enum OutputType {
     Counter,
     GFF3,
+    TSV,
 }
 
 pub trait OutWriter {
-    fn write(&self, orfs: Receiver<ORF>) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+    fn write(&self, orfs: Receiver<ORF>) -> Result<(), OrfFinderError>;
+
+    /// Called once after the ORFs of all sequences are written, e.g. to write a summary.
+    fn finish(&self) -> Result<(), OrfFinderError> {
+        return Ok(());
+    }
 }
 
+/// Creates the writer of the output format, the configuration is recorded in the header of file formats.
 pub fn get_writer(
     output_format: OutputType,
     out: Box<dyn Write + Send + Sync>,
+    config: &FinderConfig,
 ) -> Box<dyn OutWriter + Send + Sync + 'static> {
     let writer: Box<dyn OutWriter + Send + Sync> = match output_format {
-        OutputType::Counter => Box::new(CountWriter {}),
-        OutputType::GFF3 => Box::new(GffWriter::new(out)),
+        OutputType::Counter => Box::new(CountWriter::new(out)),
+        OutputType::GFF3 => Box::new(GffWriter::new(out).with_config(config)),
+        OutputType::TSV => Box::new(TsvWriter::new(out).with_config(config)),
     };
 
     return writer;
@@ -0,0 +1,256 @@
+use std::{
+    io,
+    sync::atomic::{AtomicBool, Ordering},
+    sync::Mutex,
+};
+
+use bio::io::gff;
+use crossbeam::channel::Receiver;
+
+use super::{gff_writer::insert_orf_details, outwriter::OutWriter};
+use crate::{
+    datahandler::transcript_structure::TranscriptStructure,
+    error::error::OrfFinderError,
+    finder::finder_config::FinderConfig,
+    models::models::{Direction, ORF},
+};
+
+/// Writes ORFs found on spliced transcripts as CDS features on the genome in GFF3 format.
+/// Each transcript with ORFs is written as an mRNA feature with its exons, followed by the CDS features of its ORFs.
+/// An ORF spanning several exons is written as one CDS line per exon, all sharing the ID of the ORF.
+pub struct SplicedGffWriter<T: io::Write> {
+    writer: Mutex<T>,
+    /// Configuration of the search in JSON format, written as a directive after the version
+    config_json: Option<String>,
+    /// The header is written once, before the ORFs of the first transcript
+    header_written: AtomicBool,
+}
+
+impl<T: io::Write> SplicedGffWriter<T> {
+    pub fn new(writer: T) -> Self {
+        return SplicedGffWriter {
+            writer: Mutex::new(writer),
+            config_json: None,
+            header_written: AtomicBool::new(false),
+        };
+    }
+
+    /// Records the configuration of the search in the header.
+    pub fn with_config(mut self, config: &FinderConfig) -> Self {
+        self.config_json = Some(config.to_json());
+        return self;
+    }
+
+    /// A writer of the ORFs found on a single spliced transcript.
+    /// * `transcript` - The exons of the transcript the ORFs are projected onto
+    pub fn transcript<'a>(
+        &'a self,
+        transcript: &'a TranscriptStructure,
+    ) -> SplicedTranscriptWriter<'a, T> {
+        return SplicedTranscriptWriter {
+            outputs: self,
+            transcript: transcript,
+        };
+    }
+}
+
+/// Writes the ORFs of a single spliced transcript into a `SplicedGffWriter`.
+pub struct SplicedTranscriptWriter<'a, T: io::Write> {
+    outputs: &'a SplicedGffWriter<T>,
+    transcript: &'a TranscriptStructure,
+}
+
+impl<T: io::Write> SplicedTranscriptWriter<'_, T> {
+    /// A feature of the transcript on the genome, without attributes.
+    /// * `start` - Position of the first base, starting at 0
+    /// * `end` - Position after the last base
+    fn feature(&self, feature_type: &str, start: usize, end: usize) -> gff::Record {
+        let mut gff_record = gff::Record::new();
+        *gff_record.seqname_mut() = self.transcript.seqid.clone();
+        *gff_record.feature_type_mut() = feature_type.to_string();
+        *gff_record.start_mut() = start as u64 + 1;
+        *gff_record.end_mut() = end as u64;
+        *gff_record.strand_mut() = match self.transcript.direction {
+            Direction::FORWARD => "+".to_string(),
+            Direction::REVERSE => "-".to_string(),
+        };
+
+        return gff_record;
+    }
+
+    /// The mRNA line of the transcript followed by its exon lines, the parents of the CDS lines.
+    fn transcript_records(&self) -> Vec<gff::Record> {
+        let exons = &self.transcript.exons;
+        let transcript_start = exons.first().map_or(0, |(start, _)| *start);
+        let transcript_end = exons.last().map_or(0, |(_, end)| *end);
+
+        let mut mrna = self.feature("mRNA", transcript_start, transcript_end);
+        mrna.attributes_mut()
+            .insert("ID".to_string(), self.transcript.id.clone());
+        let exon_records = exons.iter().map(|(start, end)| {
+            let mut exon = self.feature("exon", *start, *end);
+            exon.attributes_mut()
+                .insert("Parent".to_string(), self.transcript.id.clone());
+            return exon;
+        });
+
+        return std::iter::once(mrna).chain(exon_records).collect();
+    }
+
+    /// The CDS lines of the ORF, ordered by their position on the genome.
+    fn cds_records(&self, orf: &ORF) -> Vec<gff::Record> {
+        // Reverse ORFs are located on the reverse complement of the transcript
+        let transcript_len = self.transcript.len();
+        let (start, end) = match orf.direction {
+            Direction::FORWARD => (orf.start_position, orf.stop_position),
+            Direction::REVERSE => (
+                transcript_len - orf.stop_position,
+                transcript_len - orf.start_position,
+            ),
+        };
+        let direction = match orf.direction == self.transcript.direction {
+            true => Direction::FORWARD,
+            false => Direction::REVERSE,
+        };
+        let blocks = self.transcript.project(start, end);
+        let orf_uuid = uuid::Uuid::new_v4().to_string();
+
+        // The phase counts the bases to the next codon, from the start codon in the direction of the ORF
+        let mut phases = vec![0; blocks.len()];
+        let mut coding_len = 0;
+        let block_order: Vec<usize> = match direction {
+            Direction::FORWARD => (0..blocks.len()).collect(),
+            Direction::REVERSE => (0..blocks.len()).rev().collect(),
+        };
+        for index in block_order {
+            phases[index] = (3 - coding_len % 3) % 3;
+            coding_len += blocks[index].1 - blocks[index].0;
+        }
+
+        return blocks
+            .iter()
+            .zip(phases)
+            .map(|((block_start, block_end), phase)| {
+                let mut gff_record = gff::Record::new();
+                *gff_record.seqname_mut() = self.transcript.seqid.clone();
+                *gff_record.feature_type_mut() = "CDS".to_string();
+                *gff_record.start_mut() = *block_start as u64 + 1;
+                *gff_record.end_mut() = *block_end as u64;
+                *gff_record.strand_mut() = match direction {
+                    Direction::FORWARD => "+".to_string(),
+                    Direction::REVERSE => "-".to_string(),
+                };
+                *gff_record.frame_mut() = phase.to_string();
+                let attributes = gff_record.attributes_mut();
+                attributes.insert("ID".to_string(), orf_uuid.clone());
+                attributes.insert("Parent".to_string(), self.transcript.id.clone());
+                insert_orf_details(&mut gff_record, orf);
+                return gff_record;
+            })
+            .collect();
+    }
+}
+
+impl<T: io::Write> OutWriter for SplicedTranscriptWriter<'_, T> {
+    fn write(&self, orfs: Receiver<ORF>) -> Result<(), OrfFinderError> {
+        let mut writer = self.outputs.writer.lock().unwrap();
+        if !self.outputs.header_written.swap(true, Ordering::Relaxed) {
+            writeln!(writer, "##gff-version 3").map_err(OrfFinderError::OutputIo)?;
+            if let Some(config_json) = &self.outputs.config_json {
+                writeln!(writer, "#!rustyorffinder-config {}", config_json)
+                    .map_err(OrfFinderError::OutputIo)?;
+            }
+        }
+
+        let mut gff_writer = gff::Writer::new(&mut *writer, gff::GffType::GFF3);
+        for (index, orf) in orfs.iter().enumerate() {
+            let transcript_records = match index {
+                0 => self.transcript_records(),
+                _ => Vec::new(),
+            };
+            for gff_record in transcript_records.into_iter().chain(self.cds_records(&orf)) {
+                gff_writer
+                    .write(&gff_record)
+                    .map_err(|err| OrfFinderError::OutputIo(io::Error::other(err)))?;
+            }
+        }
+
+        // The GFF writer buffers the records until it is dropped
+        drop(gff_writer);
+        writer.flush().map_err(OrfFinderError::OutputIo)?;
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        datahandler::transcript_structure::TranscriptStructure,
+        models::models::{Direction, SequenceSlice, ORF},
+    };
+
+    use super::SplicedGffWriter;
+
+    #[test]
+    fn projects_orf_onto_exons() {
+        let outputs = SplicedGffWriter::new(Vec::new());
+        let transcript = TranscriptStructure {
+            id: "tx1".to_string(),
+            seqid: "chr1".to_string(),
+            direction: Direction::REVERSE,
+            exons: vec![(10, 20), (50, 60)],
+        };
+        let orf = ORF {
+            seqid: Arc::from("tx1"),
+            start_position: 2,
+            stop_position: 14,
+            sequence: SequenceSlice::from("ATGAAACCCGGG"),
+            direction: Direction::FORWARD,
+            score: None,
+            rbs: None,
+            kozak: None,
+            orf_class: None,
+            variant_effect: None,
+        };
+
+        let records = outputs.transcript(&transcript).cds_records(&orf);
+        let blocks: Vec<_> = records
+            .iter()
+            .map(|record| (*record.start(), *record.end(), record.frame().to_string()))
+            .collect();
+        // 8 bases on the second exon followed by 4 on the first, the third codon is split
+        assert_eq!(
+            blocks,
+            vec![(17, 20, "1".to_string()), (51, 58, "0".to_string())]
+        );
+        assert!(records.iter().all(|record| record.strand().is_some()));
+        assert_eq!(records[0].attributes()["ID"], records[1].attributes()["ID"]);
+        assert_eq!(records[0].attributes()["Parent"], "tx1");
+
+        let parents = outputs.transcript(&transcript).transcript_records();
+        let features: Vec<_> = parents
+            .iter()
+            .map(|record| {
+                (
+                    record.feature_type().to_string(),
+                    *record.start(),
+                    *record.end(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            features,
+            vec![
+                ("mRNA".to_string(), 11, 60),
+                ("exon".to_string(), 11, 20),
+                ("exon".to_string(), 51, 60)
+            ]
+        );
+        assert_eq!(parents[0].attributes()["ID"], "tx1");
+        assert!(parents[1..]
+            .iter()
+            .all(|record| record.attributes()["Parent"] == "tx1"));
+    }
+}
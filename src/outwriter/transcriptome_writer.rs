@@ -0,0 +1,238 @@
+use std::{
+    fs::File,
+    io,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use crossbeam::channel::Receiver;
+
+use super::outwriter::OutWriter;
+use crate::{
+    datahandler::trans_table::translate,
+    error::error::OrfFinderError,
+    models::models::{Direction, ORF},
+};
+
+/// Writes the coding regions of transcripts in the formats of TransDecoder:
+/// proteins (.pep), coding sequences (.cds), GFF3 features and BED12 lines.
+/// The ORFs of a transcript are named after it, `<transcript>.p1` is its longest ORF.
+/// Coordinates are located on the forward strand of the transcript and do not include the stop codon.
+pub struct TranscriptomeWriter<T: io::Write> {
+    pep: Mutex<T>,
+    cds: Mutex<T>,
+    gff3: Mutex<T>,
+    bed: Mutex<T>,
+    /// The GFF3 header is written once, before the ORFs of the first transcript
+    header_written: AtomicBool,
+}
+
+impl TranscriptomeWriter<File> {
+    /// Creates the files `<prefix>.pep`, `<prefix>.cds`, `<prefix>.gff3` and `<prefix>.bed`.
+    pub fn create(prefix: &Path) -> Result<Self, OrfFinderError> {
+        let create = |extension: &str| {
+            let path = format!("{}.{}", prefix.display(), extension);
+            return File::create(&path).map_err(|err| {
+                let message = format!("{}: {}", path, err);
+                OrfFinderError::OutputIo(io::Error::new(err.kind(), message))
+            });
+        };
+
+        return Ok(TranscriptomeWriter::new(
+            create("pep")?,
+            create("cds")?,
+            create("gff3")?,
+            create("bed")?,
+        ));
+    }
+}
+
+impl<T: io::Write> TranscriptomeWriter<T> {
+    pub fn new(pep: T, cds: T, gff3: T, bed: T) -> Self {
+        return TranscriptomeWriter {
+            pep: Mutex::new(pep),
+            cds: Mutex::new(cds),
+            gff3: Mutex::new(gff3),
+            bed: Mutex::new(bed),
+            header_written: AtomicBool::new(false),
+        };
+    }
+
+    /// A writer of the ORFs of a single transcript.
+    /// * `transcript_len` - Number of bases of the transcript, required to locate reverse ORFs on the forward strand
+    pub fn transcript(&self, transcript_len: usize) -> TranscriptWriter<'_, T> {
+        return TranscriptWriter {
+            outputs: self,
+            transcript_len: transcript_len,
+        };
+    }
+}
+
+/// Writes the ORFs of a single transcript into the outputs of a `TranscriptomeWriter`.
+pub struct TranscriptWriter<'a, T: io::Write> {
+    outputs: &'a TranscriptomeWriter<T>,
+    transcript_len: usize,
+}
+
+impl<T: io::Write> TranscriptWriter<'_, T> {
+    /// The 0-based, end exclusive interval of the ORF on the forward strand of the transcript.
+    fn forward_interval(&self, orf: &ORF) -> (usize, usize) {
+        return match orf.direction {
+            Direction::FORWARD => (orf.start_position, orf.stop_position),
+            Direction::REVERSE => (
+                self.transcript_len - orf.stop_position,
+                self.transcript_len - orf.start_position,
+            ),
+        };
+    }
+
+    fn write_orf(&self, orf: &ORF, name: &str) -> io::Result<()> {
+        let (start, end) = self.forward_interval(orf);
+        let strand = match orf.direction {
+            Direction::FORWARD => "+",
+            Direction::REVERSE => "-",
+        };
+        let protein = translate(orf.sequence.as_bytes());
+        let score = orf
+            .score
+            .map_or(String::new(), |score| format!(",score={:.4}", score));
+        let description = format!(
+            "type:complete len:{} ({}){} {}:{}-{}({})",
+            protein.len(),
+            strand,
+            score,
+            orf.seqid,
+            start + 1,
+            end,
+            strand
+        );
+
+        let mut pep = self.outputs.pep.lock().unwrap();
+        writeln!(pep, ">{} {}\n{}", name, description, protein)?;
+        let mut cds = self.outputs.cds.lock().unwrap();
+        writeln!(cds, ">{} {}\n{}", name, description, orf.sequence)?;
+
+        let mut gff3 = self.outputs.gff3.lock().unwrap();
+        let gff_score = orf
+            .score
+            .map_or(".".to_string(), |score| format!("{:.4}", score));
+        writeln!(
+            gff3,
+            "{}\trustyorffinder\tmRNA\t1\t{}\t.\t{}\t.\tID={};Parent={}",
+            orf.seqid, self.transcript_len, strand, name, orf.seqid
+        )?;
+        writeln!(
+            gff3,
+            "{}\trustyorffinder\tCDS\t{}\t{}\t{}\t{}\t0\tID=cds.{};Parent={}",
+            orf.seqid,
+            start + 1,
+            end,
+            gff_score,
+            strand,
+            name,
+            name
+        )?;
+
+        // The whole transcript is a single block, the coding region is drawn thick
+        let mut bed = self.outputs.bed.lock().unwrap();
+        writeln!(
+            bed,
+            "{}\t0\t{}\t{}\t0\t{}\t{}\t{}\t0\t1\t{},\t0,",
+            orf.seqid, self.transcript_len, name, strand, start, end, self.transcript_len
+        )?;
+
+        return Ok(());
+    }
+}
+
+impl<T: io::Write> OutWriter for TranscriptWriter<'_, T> {
+    fn write(&self, orfs: Receiver<ORF>) -> Result<(), OrfFinderError> {
+        if !self.outputs.header_written.swap(true, Ordering::Relaxed) {
+            let mut gff3 = self.outputs.gff3.lock().unwrap();
+            writeln!(gff3, "##gff-version 3").map_err(OrfFinderError::OutputIo)?;
+        }
+
+        // Numbered from the longest ORF, independent of the order the ORFs arrive in
+        let mut orfs: Vec<ORF> = orfs.iter().collect();
+        orfs.sort_by(|a, b| {
+            b.sequence
+                .len()
+                .cmp(&a.sequence.len())
+                .then(a.start_position.cmp(&b.start_position))
+        });
+
+        for (index, orf) in orfs.iter().enumerate() {
+            let name = format!("{}.p{}", orf.seqid, index + 1);
+            self.write_orf(orf, &name)
+                .map_err(OrfFinderError::OutputIo)?;
+        }
+
+        for output in [
+            &self.outputs.pep,
+            &self.outputs.cds,
+            &self.outputs.gff3,
+            &self.outputs.bed,
+        ] {
+            output
+                .lock()
+                .unwrap()
+                .flush()
+                .map_err(OrfFinderError::OutputIo)?;
+        }
+
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        models::models::{Direction, SequenceSlice, ORF},
+        outwriter::outwriter::OutWriter,
+    };
+
+    use super::TranscriptomeWriter;
+
+    #[test]
+    fn writes_reverse_orf_on_forward_strand() {
+        let outputs = TranscriptomeWriter::new(Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        let (sender, receiver) = crossbeam::channel::unbounded();
+        sender
+            .send(ORF {
+                seqid: Arc::from("tx1"),
+                start_position: 2,
+                stop_position: 11,
+                sequence: SequenceSlice::from("ATGAAATGG"),
+                direction: Direction::REVERSE,
+                score: None,
+                rbs: None,
+                kozak: None,
+                orf_class: None,
+                variant_effect: None,
+            })
+            .unwrap();
+        drop(sender);
+
+        outputs.transcript(20).write(receiver).unwrap();
+
+        let output = |buffer: &std::sync::Mutex<Vec<u8>>| {
+            return String::from_utf8(buffer.lock().unwrap().clone()).unwrap();
+        };
+        assert_eq!(
+            output(&outputs.pep),
+            ">tx1.p1 type:complete len:3 (-) tx1:10-18(-)\nMKW\n"
+        );
+        assert!(output(&outputs.cds).ends_with("\nATGAAATGG\n"));
+        assert!(output(&outputs.gff3)
+            .contains("tx1\trustyorffinder\tCDS\t10\t18\t.\t-\t0\tID=cds.tx1.p1;Parent=tx1.p1\n"));
+        assert_eq!(
+            output(&outputs.bed),
+            "tx1\t0\t20\ttx1.p1\t0\t-\t9\t18\t0\t1\t20,\t0,\n"
+        );
+    }
+}
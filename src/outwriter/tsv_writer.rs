@@ -0,0 +1,109 @@
+use std::{
+    io,
+    sync::atomic::{AtomicBool, Ordering},
+    sync::Mutex,
+};
+
+use super::outwriter::OutWriter;
+use crate::{
+    error::error::OrfFinderError, finder::finder_config::FinderConfig, models::models::Direction,
+};
+
+pub struct TsvWriter<T: io::Write> {
+    writer: Mutex<T>,
+    /// Configuration of the search in JSON format, written as a comment before the column names
+    config_json: Option<String>,
+    /// The header is written once, before the ORFs of the first sequence
+    header_written: AtomicBool,
+}
+
+impl<T: io::Write> TsvWriter<T> {
+    pub fn new(writer: T) -> Self {
+        return TsvWriter {
+            writer: Mutex::new(writer),
+            config_json: None,
+            header_written: AtomicBool::new(false),
+        };
+    }
+
+    /// Records the configuration of the search in the header.
+    pub fn with_config(mut self, config: &FinderConfig) -> Self {
+        self.config_json = Some(config.to_json());
+        return self;
+    }
+}
+
+impl<T: io::Write> OutWriter for TsvWriter<T> {
+    fn write(
+        &self,
+        orfs: crossbeam::channel::Receiver<crate::models::models::ORF>,
+    ) -> Result<(), OrfFinderError> {
+        let mut writer = self.writer.lock().unwrap();
+        if !self.header_written.swap(true, Ordering::Relaxed) {
+            if let Some(config_json) = &self.config_json {
+                writeln!(writer, "# rustyorffinder-config {}", config_json)
+                    .map_err(OrfFinderError::OutputIo)?;
+            }
+            writeln!(
+                writer,
+                "seqid\tstart\tstop\tstrand\tlength\tscore\trbs_motif\trbs_spacer\tkozak_context\tkozak_strength\torf_class\tvariant_effect\tvariant_ids\tsequence"
+            )
+            .map_err(OrfFinderError::OutputIo)?;
+        }
+
+        for orf in orfs {
+            let strand = match orf.direction {
+                Direction::FORWARD => "+",
+                Direction::REVERSE => "-",
+            };
+            let score = match orf.score {
+                Some(score) => format!("{:.4}", score),
+                None => ".".to_string(),
+            };
+            let (rbs_motif, rbs_spacer) = match orf.rbs {
+                Some(rbs) => (rbs.motif, rbs.spacer.to_string()),
+                None => (".".to_string(), ".".to_string()),
+            };
+            let (kozak_context, kozak_strength) = match orf.kozak {
+                Some(kozak) => (
+                    kozak.context,
+                    format!("{:?}", kozak.strength).to_lowercase(),
+                ),
+                None => (".".to_string(), ".".to_string()),
+            };
+            let orf_class = orf
+                .orf_class
+                .map_or(".".to_string(), |orf_class| orf_class.to_string());
+            let (variant_effect, variant_ids) = match orf.variant_effect {
+                Some(effect) if effect.variant_ids.is_empty() => {
+                    (effect.kind.to_string(), ".".to_string())
+                }
+                Some(effect) => (effect.kind.to_string(), effect.variant_ids.join(",")),
+                None => (".".to_string(), ".".to_string()),
+            };
+
+            writeln!(
+                writer,
+                "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                orf.seqid,
+                orf.start_position,
+                orf.stop_position,
+                strand,
+                orf.sequence.len(),
+                score,
+                rbs_motif,
+                rbs_spacer,
+                kozak_context,
+                kozak_strength,
+                orf_class,
+                variant_effect,
+                variant_ids,
+                orf.sequence
+            )
+            .map_err(OrfFinderError::OutputIo)?;
+        }
+
+        writer.flush().map_err(OrfFinderError::OutputIo)?;
+        return Ok(());
+    }
+}
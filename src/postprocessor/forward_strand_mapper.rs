@@ -0,0 +1,96 @@
+use crossbeam::channel::{Receiver, Sender};
+
+use crate::{
+    error::error::OrfFinderError,
+    models::models::{Direction, ORF},
+};
+
+use super::postprocessor::PostProcessor;
+
+/// Locates reverse ORFs on the forward strand, e.g. before writing them with genome coordinates.
+/// Reverse ORFs are found on the reverse complement, afterwards their positions span the same bases
+/// as forward ORFs do, from the base following the stop codon to the last base of the start codon.
+/// As all other stages expect positions on the strand of the ORF, this has to be the last stage.
+pub struct ForwardStrandMapper {
+    sequence_length: usize,
+}
+
+impl ForwardStrandMapper {
+    /// * `sequence_length` - Length of the sequence the reverse ORFs are positioned on, e.g. the whole chromosome of a region
+    pub fn new(sequence_length: usize) -> Self {
+        return ForwardStrandMapper {
+            sequence_length: sequence_length,
+        };
+    }
+
+    pub fn map(&self, mut orf: ORF) -> ORF {
+        if orf.direction == Direction::REVERSE {
+            (orf.start_position, orf.stop_position) = (
+                self.sequence_length - orf.stop_position,
+                self.sequence_length - orf.start_position,
+            );
+        }
+
+        return orf;
+    }
+}
+
+impl PostProcessor for ForwardStrandMapper {
+    fn process(&self, orfs: Receiver<ORF>, orf_sender: Sender<ORF>) -> Result<(), OrfFinderError> {
+        for orf in orfs {
+            orf_sender.send(self.map(orf))?;
+        }
+
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        datahandler::filehandler::{RecordOffset, SequenceRecord},
+        find_orfs_in_records,
+        finder::finder_config::FinderConfig,
+        outwriter::outwriter::OutputType,
+        test_utils::SharedBuffer,
+    };
+
+    #[test]
+    fn writes_reverse_orfs_of_regions_on_the_forward_strand() {
+        // The reverse ORF ATG AAA CCC TAA covers bases 10 to 22 of the chromosome, its stop codon 10 to 13
+        let chromosome = format!("{}TTAGGGTTTCAT{}", "C".repeat(10), "C".repeat(18));
+        let region = SequenceRecord {
+            id: "chr".to_string(),
+            sequence: chromosome[5..30].to_string(),
+            packed: None,
+            offset: Some(RecordOffset {
+                start: 5,
+                source_len: chromosome.len(),
+            }),
+        };
+        let config = FinderConfig::builder().min_len(3).build().unwrap();
+        let out_target = SharedBuffer::default();
+
+        find_orfs_in_records(
+            vec![Ok(region)],
+            &config,
+            OutputType::GFF3,
+            Box::new(out_target.clone()),
+            |_| {
+                return Ok(vec![]);
+            },
+        )
+        .unwrap();
+
+        let gff = out_target.contents();
+        let features: Vec<(&str, &str, &str)> = gff
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .map(|line| {
+                let columns: Vec<&str> = line.split('\t').collect();
+                return (columns[3], columns[4], columns[6]);
+            })
+            .collect();
+        assert_eq!(features, vec![("14", "22", "-")]);
+    }
+}
@@ -0,0 +1,6 @@
+pub mod forward_strand_mapper;
+pub mod orf_classifier;
+pub mod overlap_resolver;
+pub mod postprocessor;
+pub mod score_filter;
+pub mod transcript_selector;
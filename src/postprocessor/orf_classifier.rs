@@ -0,0 +1,135 @@
+use crossbeam::channel::{Receiver, Sender};
+
+use crate::{
+    datahandler::cds_annotation::AnnotatedCds,
+    error::error::OrfFinderError,
+    models::models::{Direction, OrfClass, ORF},
+};
+
+use super::postprocessor::PostProcessor;
+
+/// Classifies the ORFs of a transcript by their location relative to its annotated CDS.
+/// ORFs on the opposite strand of the CDS are passed on without a class.
+pub struct OrfClassifier {
+    /// Start and end of the CDS on its own strand
+    cds_start: usize,
+    cds_end: usize,
+    direction: Direction,
+}
+
+impl OrfClassifier {
+    /// * `cds` - The annotated CDS of the transcript
+    /// * `sequence_len` - Number of bases of the transcript, required to locate the CDS on the reverse strand
+    pub fn new(cds: AnnotatedCds, sequence_len: usize) -> Self {
+        let (cds_start, cds_end) = match cds.direction {
+            Direction::FORWARD => (cds.start, cds.end),
+            Direction::REVERSE => (sequence_len - cds.end, sequence_len - cds.start),
+        };
+
+        return OrfClassifier {
+            cds_start: cds_start,
+            cds_end: cds_end,
+            direction: cds.direction,
+        };
+    }
+
+    fn classify(&self, orf: &ORF) -> Option<OrfClass> {
+        if orf.direction != self.direction {
+            return None;
+        }
+
+        let start = orf.start_position;
+        let in_frame = start.abs_diff(self.cds_start).is_multiple_of(3);
+        let class = if start < self.cds_start {
+            match (orf.stop_position <= self.cds_start, in_frame) {
+                (true, _) => OrfClass::Uorf,
+                // An ORF in the frame of the CDS can only stop at the stop codon of the CDS
+                (false, true) => OrfClass::NTerminalExtension,
+                (false, false) => OrfClass::OverlappingUorf,
+            }
+        } else if start < self.cds_end {
+            match (start == self.cds_start, in_frame) {
+                (true, _) => OrfClass::AnnotatedCds,
+                (false, true) => OrfClass::NTerminalTruncation,
+                (false, false) => OrfClass::InternalOutOfFrame,
+            }
+        } else {
+            OrfClass::Dorf
+        };
+
+        return Some(class);
+    }
+}
+
+impl PostProcessor for OrfClassifier {
+    fn process(&self, orfs: Receiver<ORF>, orf_sender: Sender<ORF>) -> Result<(), OrfFinderError> {
+        for mut orf in orfs {
+            orf.orf_class = self.classify(&orf);
+            orf_sender.send(orf)?;
+        }
+
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        datahandler::cds_annotation::AnnotatedCds,
+        models::models::{Direction, OrfClass, SequenceSlice, ORF},
+    };
+
+    use super::OrfClassifier;
+
+    fn orf(start_position: usize, stop_position: usize, direction: Direction) -> ORF {
+        return ORF {
+            seqid: Arc::from("transcript"),
+            start_position: start_position,
+            stop_position: stop_position,
+            sequence: SequenceSlice::from("A".repeat(stop_position - start_position)),
+            direction: direction,
+            score: None,
+            rbs: None,
+            kozak: None,
+            orf_class: None,
+            variant_effect: None,
+        };
+    }
+
+    #[test]
+    fn classifies_orfs_relative_to_the_cds() {
+        let cds = AnnotatedCds {
+            start: 100,
+            end: 400,
+            direction: Direction::FORWARD,
+        };
+        let classifier = OrfClassifier::new(cds, 600);
+        let class = |start, stop| classifier.classify(&orf(start, stop, Direction::FORWARD));
+
+        assert_eq!(class(10, 40), Some(OrfClass::Uorf));
+        assert_eq!(class(11, 150), Some(OrfClass::OverlappingUorf));
+        assert_eq!(class(40, 397), Some(OrfClass::NTerminalExtension));
+        assert_eq!(class(100, 397), Some(OrfClass::AnnotatedCds));
+        assert_eq!(class(160, 397), Some(OrfClass::NTerminalTruncation));
+        assert_eq!(class(161, 450), Some(OrfClass::InternalOutOfFrame));
+        assert_eq!(class(450, 510), Some(OrfClass::Dorf));
+        assert_eq!(classifier.classify(&orf(10, 40, Direction::REVERSE)), None);
+    }
+
+    #[test]
+    fn locates_reverse_cds_on_its_strand() {
+        let cds = AnnotatedCds {
+            start: 200,
+            end: 500,
+            direction: Direction::REVERSE,
+        };
+        let classifier = OrfClassifier::new(cds, 600);
+        let class = |start, stop| classifier.classify(&orf(start, stop, Direction::REVERSE));
+
+        assert_eq!(class(10, 60), Some(OrfClass::Uorf));
+        assert_eq!(class(100, 397), Some(OrfClass::AnnotatedCds));
+        assert_eq!(class(450, 510), Some(OrfClass::Dorf));
+    }
+}
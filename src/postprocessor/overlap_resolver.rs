@@ -0,0 +1,248 @@
+use std::collections::BTreeSet;
+
+use crossbeam::channel::{Receiver, Sender};
+
+use crate::{
+    error::error::OrfFinderError,
+    models::models::{Direction, ORF},
+};
+
+use super::postprocessor::PostProcessor;
+
+/// Maximum overlap between genes on the same strand, as used by Prodigal
+pub const DEFAULT_MAX_SAME_STRAND_OVERLAP: usize = 60;
+/// Maximum overlap between genes on opposite strands, as used by Prodigal
+pub const DEFAULT_MAX_OPPOSITE_STRAND_OVERLAP: usize = 200;
+
+/// Resolves overlapping ORFs across all frames and strands.
+/// ORFs are accepted greedily from the longest to the shortest, an ORF is dropped if it overlaps an already
+/// accepted ORF by more than the allowed number of bases for the respective strand combination.
+/// Setting both maximum overlaps to 0 keeps only the longest ORF of each overlapping cluster.
+pub struct OverlapResolver {
+    sequence_length: usize,
+    max_same_strand_overlap: usize,
+    max_opposite_strand_overlap: usize,
+}
+
+impl OverlapResolver {
+    /// * `sequence_length` - Length of the searched sequence, required to map reverse ORFs onto the forward strand
+    /// * `max_same_strand_overlap` - Maximum overlap in bases between two ORFs on the same strand
+    /// * `max_opposite_strand_overlap` - Maximum overlap in bases between two ORFs on opposite strands
+    pub fn new(
+        sequence_length: usize,
+        max_same_strand_overlap: usize,
+        max_opposite_strand_overlap: usize,
+    ) -> Self {
+        return OverlapResolver {
+            sequence_length: sequence_length,
+            max_same_strand_overlap: max_same_strand_overlap,
+            max_opposite_strand_overlap: max_opposite_strand_overlap,
+        };
+    }
+
+    /// Reverse ORFs are positioned on the reverse complement, this maps them back onto the forward strand.
+    /// ORFs spanning the origin of circular sequences end past the sequence length, so that each interval
+    /// starts within the sequence and its end is never before its start.
+    fn forward_interval(&self, orf: &ORF) -> (usize, usize) {
+        let length = self.sequence_length;
+        let stop_position = match orf.start_position <= orf.stop_position {
+            true => orf.stop_position,
+            false => orf.stop_position + length,
+        };
+
+        return match orf.direction {
+            Direction::FORWARD => (orf.start_position, stop_position),
+            Direction::REVERSE => {
+                let start = (2 * length).saturating_sub(stop_position);
+                let end = (2 * length).saturating_sub(orf.start_position);
+                match start >= length {
+                    true => (start - length, end - length),
+                    false => (start, end),
+                }
+            }
+        };
+    }
+
+    /// Overlap in bases of two forward intervals, including the parts wrapping around the origin.
+    fn overlap(&self, interval: (usize, usize), other: (usize, usize)) -> usize {
+        let length = self.sequence_length;
+        let overlap = |(start, end): (usize, usize), (other_start, other_end): (usize, usize)| {
+            return end.min(other_end).saturating_sub(start.max(other_start));
+        };
+
+        return overlap(interval, other)
+            + overlap((interval.0 + length, interval.1 + length), other)
+            + overlap(interval, (other.0 + length, other.1 + length));
+    }
+
+    fn resolve(&self, orfs: Vec<ORF>) -> Vec<ORF> {
+        let mut candidates: Vec<(usize, usize, bool, ORF)> = orfs
+            .into_iter()
+            .map(|orf| {
+                let (start, end) = self.forward_interval(&orf);
+                let is_reverse = matches!(orf.direction, Direction::REVERSE);
+                (start, end, is_reverse, orf)
+            })
+            .collect();
+
+        // Longest first, the remaining keys only make the order independent of the thread scheduling
+        candidates.sort_by(|a, b| {
+            (b.1 - b.0)
+                .cmp(&(a.1 - a.0))
+                .then(a.0.cmp(&b.0))
+                .then(a.2.cmp(&b.2))
+        });
+
+        let mut accepted = BTreeSet::new();
+        let mut max_accepted_len = 0;
+        let mut resolved = Vec::new();
+
+        for (start, end, is_reverse, orf) in candidates {
+            // Any accepted ORF overlapping this one has to start within the longest accepted length before it.
+            // On circular sequences it may also wrap around the origin onto this one, or this one onto it.
+            let length = self.sequence_length;
+            let ranges = [
+                (start.saturating_sub(max_accepted_len), end),
+                (
+                    (start + length).saturating_sub(max_accepted_len),
+                    end + length,
+                ),
+                (0, end.saturating_sub(length)),
+            ];
+            let overlapping: BTreeSet<(usize, usize, bool)> = ranges
+                .into_iter()
+                .filter(|(range_start, range_end)| range_start < range_end)
+                .flat_map(|(range_start, range_end)| {
+                    accepted.range((range_start, 0, false)..(range_end, 0, false))
+                })
+                .copied()
+                .collect();
+            let has_conflict =
+                overlapping
+                    .into_iter()
+                    .any(|(other_start, other_end, other_is_reverse)| {
+                        let overlap = self.overlap((start, end), (other_start, other_end));
+                        let max_overlap = match is_reverse == other_is_reverse {
+                            true => self.max_same_strand_overlap,
+                            false => self.max_opposite_strand_overlap,
+                        };
+
+                        overlap > max_overlap
+                    });
+
+            if has_conflict {
+                continue;
+            }
+
+            accepted.insert((start, end, is_reverse));
+            max_accepted_len = max_accepted_len.max(end - start);
+            resolved.push((start, orf));
+        }
+
+        resolved.sort_by_key(|(start, _)| *start);
+
+        return resolved.into_iter().map(|(_, orf)| orf).collect();
+    }
+}
+
+impl PostProcessor for OverlapResolver {
+    fn process(&self, orfs: Receiver<ORF>, orf_sender: Sender<ORF>) -> Result<(), OrfFinderError> {
+        // Overlaps can only be resolved once all ORFs are known
+        let orfs: Vec<ORF> = orfs.iter().collect();
+
+        for orf in self.resolve(orfs) {
+            orf_sender.send(orf)?;
+        }
+
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::models::models::{Direction, SequenceSlice, ORF};
+
+    use super::OverlapResolver;
+
+    fn orf(start_position: usize, stop_position: usize, direction: Direction) -> ORF {
+        return ORF {
+            seqid: Arc::from("sequence"),
+            start_position: start_position,
+            stop_position: stop_position,
+            sequence: SequenceSlice::from(""),
+            direction: direction,
+            score: None,
+            rbs: None,
+            kozak: None,
+            orf_class: None,
+            variant_effect: None,
+        };
+    }
+
+    #[test]
+    fn keeps_longest_of_nested_orfs() {
+        let resolver = OverlapResolver::new(1000, 0, 0);
+        let orfs = vec![
+            orf(300, 600, Direction::FORWARD),
+            orf(100, 600, Direction::FORWARD),
+            orf(200, 600, Direction::FORWARD),
+        ];
+
+        let resolved = resolver.resolve(orfs);
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].start_position, 100);
+    }
+
+    #[test]
+    fn allows_configured_overlap() {
+        let resolver = OverlapResolver::new(1000, 60, 0);
+        let orfs = vec![
+            orf(0, 300, Direction::FORWARD),
+            orf(240, 500, Direction::FORWARD),
+            orf(420, 700, Direction::FORWARD),
+        ];
+
+        let resolved = resolver.resolve(orfs);
+        let starts: Vec<usize> = resolved.iter().map(|orf| orf.start_position).collect();
+        assert_eq!(starts, vec![0, 420]);
+    }
+
+    #[test]
+    fn maps_reverse_orfs_onto_forward_strand() {
+        let resolver = OverlapResolver::new(1000, 0, 50);
+        // Covers 700..900 on the forward strand
+        let reverse = orf(100, 300, Direction::REVERSE);
+        let overlapping = orf(750, 850, Direction::FORWARD);
+        let adjacent = orf(860, 950, Direction::FORWARD);
+
+        let resolved = resolver.resolve(vec![reverse, overlapping, adjacent]);
+        assert_eq!(resolved.len(), 2);
+        assert!(matches!(resolved[0].direction, Direction::REVERSE));
+        assert_eq!(resolved[1].start_position, 860);
+    }
+
+    #[test]
+    fn resolves_orfs_spanning_the_origin() {
+        let resolver = OverlapResolver::new(1000, 0, 0);
+        // Covers 900..1000 and 0..100 on the forward strand
+        let wrapping = orf(900, 100, Direction::FORWARD);
+        let overlapping_start = orf(50, 120, Direction::FORWARD);
+        // Covers 950..1000 and 0..30 on the forward strand
+        let overlapping_reverse = orf(970, 50, Direction::REVERSE);
+        let adjacent = orf(100, 800, Direction::FORWARD);
+
+        let resolved = resolver.resolve(vec![
+            overlapping_start,
+            wrapping,
+            overlapping_reverse,
+            adjacent,
+        ]);
+        let positions: Vec<(usize, usize)> = resolved
+            .iter()
+            .map(|orf| (orf.start_position, orf.stop_position))
+            .collect();
+        assert_eq!(positions, vec![(100, 800), (900, 100)]);
+    }
+}
@@ -0,0 +1,9 @@
+use crossbeam::channel::{Receiver, Sender};
+
+use crate::{error::error::OrfFinderError, models::models::ORF};
+
+/// A stage between the transcriber threads and the `OutWriter`.
+/// Each post processor receives the ORFs of the previous stage and sends the ORFs it keeps to the next one.
+pub trait PostProcessor {
+    fn process(&self, orfs: Receiver<ORF>, orf_sender: Sender<ORF>) -> Result<(), OrfFinderError>;
+}
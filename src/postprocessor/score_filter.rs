@@ -0,0 +1,40 @@
+use crossbeam::channel::{Receiver, Sender};
+
+use crate::{error::error::OrfFinderError, models::models::ORF, scoring::scorer::Scorer};
+
+use super::postprocessor::PostProcessor;
+
+/// Attaches the coding potential to each ORF and drops all ORFs scoring below the threshold.
+pub struct ScoreFilter {
+    scorer: Box<dyn Scorer + Send + Sync>,
+    min_score: Option<f64>,
+}
+
+impl ScoreFilter {
+    /// * `scorer` - The scorer calculating the coding potential
+    /// * `min_score` - Minimum score of an ORF to be kept, all ORFs are kept if none is given
+    pub fn new(scorer: Box<dyn Scorer + Send + Sync>, min_score: Option<f64>) -> Self {
+        return ScoreFilter {
+            scorer: scorer,
+            min_score: min_score,
+        };
+    }
+}
+
+impl PostProcessor for ScoreFilter {
+    fn process(&self, orfs: Receiver<ORF>, orf_sender: Sender<ORF>) -> Result<(), OrfFinderError> {
+        for mut orf in orfs {
+            let score = self.scorer.score_orf(&orf);
+            if let Some(min_score) = self.min_score {
+                if score < min_score {
+                    continue;
+                }
+            }
+
+            orf.score = Some(score);
+            orf_sender.send(orf)?;
+        }
+
+        return Ok(());
+    }
+}
@@ -0,0 +1,152 @@
+use crossbeam::channel::{Receiver, Sender};
+
+use crate::{
+    error::error::OrfFinderError,
+    models::models::{Direction, ORF},
+};
+
+use super::postprocessor::PostProcessor;
+
+/// Selects the best candidate coding regions of a transcript, similar to TransDecoder.
+/// The strand with the best scoring ORF is chosen, or the strand of the longest ORF if the ORFs are not scored.
+/// On that strand the longest ORFs that do not overlap a longer ORF are kept, at most `max_orfs`.
+pub struct TranscriptSelector {
+    max_orfs: usize,
+}
+
+impl TranscriptSelector {
+    /// * `max_orfs` - Maximum number of ORFs kept per transcript
+    pub fn new(max_orfs: usize) -> Self {
+        return TranscriptSelector { max_orfs: max_orfs };
+    }
+
+    /// Forward if the strands are equally good.
+    fn best_strand(orfs: &[ORF]) -> Direction {
+        let best = |direction: Direction| {
+            let strand_orfs = orfs.iter().filter(|orf| orf.direction == direction);
+            let best_score = strand_orfs
+                .clone()
+                .filter_map(|orf| orf.score)
+                .reduce(f64::max);
+            let longest = strand_orfs.map(|orf| orf.sequence.len()).max();
+            return (best_score, longest);
+        };
+
+        let (forward_score, forward_len) = best(Direction::FORWARD);
+        let (reverse_score, reverse_len) = best(Direction::REVERSE);
+        let reverse_is_better = match (forward_score, reverse_score) {
+            (Some(forward_score), Some(reverse_score)) => reverse_score > forward_score,
+            (None, Some(_)) => true,
+            (Some(_), None) => false,
+            (None, None) => reverse_len > forward_len,
+        };
+
+        return match reverse_is_better {
+            true => Direction::REVERSE,
+            false => Direction::FORWARD,
+        };
+    }
+
+    fn select(&self, orfs: Vec<ORF>) -> Vec<ORF> {
+        let strand = TranscriptSelector::best_strand(&orfs);
+        let mut candidates: Vec<ORF> = orfs
+            .into_iter()
+            .filter(|orf| orf.direction == strand)
+            .collect();
+        // Longest first, ties are broken by the position to be independent of the thread scheduling
+        candidates.sort_by(|a, b| {
+            b.sequence
+                .len()
+                .cmp(&a.sequence.len())
+                .then(a.start_position.cmp(&b.start_position))
+        });
+
+        let mut selected: Vec<ORF> = Vec::new();
+        for orf in candidates {
+            if selected.len() == self.max_orfs {
+                break;
+            }
+            let overlaps = selected.iter().any(|other| {
+                orf.start_position < other.stop_position && other.start_position < orf.stop_position
+            });
+            if !overlaps {
+                selected.push(orf);
+            }
+        }
+
+        return selected;
+    }
+}
+
+impl PostProcessor for TranscriptSelector {
+    fn process(&self, orfs: Receiver<ORF>, orf_sender: Sender<ORF>) -> Result<(), OrfFinderError> {
+        // The strand can only be chosen once all ORFs of the transcript are known
+        let orfs: Vec<ORF> = orfs.iter().collect();
+
+        for orf in self.select(orfs) {
+            orf_sender.send(orf)?;
+        }
+
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::models::models::{Direction, SequenceSlice, ORF};
+
+    use super::TranscriptSelector;
+
+    fn orf(start_position: usize, stop_position: usize, direction: Direction) -> ORF {
+        return ORF {
+            seqid: Arc::from("transcript"),
+            start_position: start_position,
+            stop_position: stop_position,
+            sequence: SequenceSlice::from("A".repeat(stop_position - start_position)),
+            direction: direction,
+            score: None,
+            rbs: None,
+            kozak: None,
+            orf_class: None,
+            variant_effect: None,
+        };
+    }
+
+    fn positions(orfs: &[ORF]) -> Vec<(usize, usize)> {
+        return orfs
+            .iter()
+            .map(|orf| (orf.start_position, orf.stop_position))
+            .collect();
+    }
+
+    #[test]
+    fn keeps_longest_orfs_of_the_longer_strand() {
+        let orfs = vec![
+            orf(0, 300, Direction::FORWARD),
+            orf(30, 300, Direction::FORWARD),
+            orf(400, 550, Direction::FORWARD),
+            orf(0, 360, Direction::REVERSE),
+        ];
+
+        let selected = TranscriptSelector::new(2).select(orfs);
+        assert_eq!(positions(&selected), vec![(0, 360)]);
+    }
+
+    #[test]
+    fn scores_choose_the_strand() {
+        let mut forward = vec![
+            orf(0, 300, Direction::FORWARD),
+            orf(30, 300, Direction::FORWARD),
+            orf(400, 550, Direction::FORWARD),
+        ];
+        forward[2].score = Some(2.0);
+        let mut reverse = orf(0, 360, Direction::REVERSE);
+        reverse.score = Some(1.0);
+        forward.push(reverse);
+
+        let selected = TranscriptSelector::new(2).select(forward);
+        assert_eq!(positions(&selected), vec![(0, 300), (400, 550)]);
+    }
+}
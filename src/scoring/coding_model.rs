@@ -0,0 +1,34 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::error::OrfFinderError;
+
+/// Frequencies describing coding sequences of an organism, all maps are keyed by the uppercase DNA word.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CodingModel {
+    /// Relative frequency of each codon in coding sequences
+    #[serde(default)]
+    pub codon_frequencies: HashMap<String, f64>,
+    /// Relative frequency of each in-frame hexamer in coding sequences
+    #[serde(default)]
+    pub coding_hexamer_frequencies: HashMap<String, f64>,
+    /// Relative frequency of each hexamer in non-coding sequences
+    #[serde(default)]
+    pub noncoding_hexamer_frequencies: HashMap<String, f64>,
+}
+
+pub fn parse_coding_model(path: PathBuf) -> Result<CodingModel, OrfFinderError> {
+    let model_error = |err: &dyn std::fmt::Display| {
+        OrfFinderError::Model(format!(
+            "could not read the coding model {}: {}",
+            path.display(),
+            err
+        ))
+    };
+
+    let model_file = std::fs::File::open(path.as_path()).map_err(|err| model_error(&err))?;
+    let coding_model = serde_json::from_reader(model_file).map_err(|err| model_error(&err))?;
+
+    return Ok(coding_model);
+}
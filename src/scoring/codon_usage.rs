@@ -0,0 +1,91 @@
+use crate::datahandler::trans_table::codon_index;
+
+use super::{
+    coding_model::CodingModel,
+    scorer::{base_frequencies, Scorer},
+};
+
+const BASES: [u8; 4] = [b'A', b'C', b'G', b'T'];
+const PSEUDO_FREQUENCY: f64 = 1e-4;
+
+/// Scores ORFs by the log-likelihood ratio of their codons under the coding model
+/// against a background model derived from the base composition of the searched sequence.
+pub struct CodonUsageScorer {
+    log_odds: [f64; 64],
+}
+
+impl CodonUsageScorer {
+    pub fn new(model: &CodingModel, sequence: &[u8]) -> Self {
+        let background = base_frequencies(sequence);
+
+        let mut coding_frequencies = [0.0; 64];
+        let mut background_frequencies = [0.0; 64];
+        for (index, codon) in all_codons().iter().enumerate() {
+            let frequency = model.codon_frequencies.get(codon).copied().unwrap_or(0.0);
+            coding_frequencies[index] = frequency + PSEUDO_FREQUENCY;
+            background_frequencies[index] = codon.bytes().map(|base| background[&base]).product();
+        }
+
+        let total: f64 = coding_frequencies.iter().sum();
+        let mut log_odds = [0.0; 64];
+        for index in 0..64 {
+            log_odds[index] =
+                (coding_frequencies[index] / total).ln() - background_frequencies[index].ln();
+        }
+
+        return CodonUsageScorer { log_odds: log_odds };
+    }
+}
+
+impl Scorer for CodonUsageScorer {
+    fn score(&self, sequence: &[u8]) -> f64 {
+        return sequence
+            .chunks_exact(3)
+            .filter_map(codon_index)
+            .map(|index| self.log_odds[index])
+            .sum();
+    }
+}
+
+/// All 64 codons ordered by their `codon_index`.
+pub fn all_codons() -> Vec<String> {
+    let mut codons = Vec::with_capacity(64);
+    for first in BASES {
+        for second in BASES {
+            for third in BASES {
+                codons.push(String::from_utf8(vec![first, second, third]).unwrap());
+            }
+        }
+    }
+
+    return codons;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        datahandler::trans_table::codon_index,
+        scoring::{coding_model::CodingModel, scorer::Scorer},
+    };
+
+    use super::{all_codons, CodonUsageScorer};
+
+    #[test]
+    fn codon_index_matches_codon_order() {
+        for (index, codon) in all_codons().iter().enumerate() {
+            assert_eq!(codon_index(codon.as_bytes()), Some(index));
+        }
+        assert_eq!(codon_index(b"ANA"), None);
+    }
+
+    #[test]
+    fn preferred_codons_score_higher() {
+        let mut model = CodingModel::default();
+        model.codon_frequencies.insert("ATG".to_string(), 0.5);
+        model.codon_frequencies.insert("GCC".to_string(), 0.5);
+
+        let scorer = CodonUsageScorer::new(&model, b"ACGTACGTACGT");
+        assert!(scorer.score(b"ATGGCCGCC") > 0.0);
+        assert!(scorer.score(b"ATGTTTTTT") < scorer.score(b"ATGGCCGCC"));
+    }
+}
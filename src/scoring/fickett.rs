@@ -0,0 +1,86 @@
+use super::scorer::Scorer;
+
+const BASES: [u8; 4] = [b'A', b'C', b'G', b'T'];
+
+// Lookup tables of Fickett (1982), Nucleic Acids Research 10(17), in the order A, C, G, T
+const POSITION_THRESHOLDS: [f64; 10] = [1.9, 1.8, 1.7, 1.6, 1.5, 1.4, 1.3, 1.2, 1.1, 0.0];
+const POSITION_PROBABILITIES: [[f64; 10]; 4] = [
+    [0.94, 0.68, 0.84, 0.93, 0.58, 0.68, 0.45, 0.34, 0.20, 0.22],
+    [0.80, 0.70, 0.70, 0.81, 0.66, 0.48, 0.51, 0.33, 0.30, 0.23],
+    [0.90, 0.88, 0.74, 0.64, 0.53, 0.48, 0.27, 0.16, 0.08, 0.08],
+    [0.97, 0.97, 0.91, 0.68, 0.69, 0.44, 0.54, 0.20, 0.09, 0.09],
+];
+const POSITION_WEIGHTS: [f64; 4] = [0.26, 0.18, 0.31, 0.33];
+
+const CONTENT_THRESHOLDS: [f64; 10] = [0.33, 0.31, 0.29, 0.27, 0.25, 0.23, 0.21, 0.19, 0.17, 0.0];
+const CONTENT_PROBABILITIES: [[f64; 10]; 4] = [
+    [0.28, 0.49, 0.44, 0.55, 0.62, 0.49, 0.67, 0.65, 0.81, 0.21],
+    [0.82, 0.64, 0.51, 0.64, 0.59, 0.59, 0.43, 0.44, 0.39, 0.31],
+    [0.40, 0.54, 0.47, 0.64, 0.64, 0.73, 0.41, 0.41, 0.33, 0.29],
+    [0.28, 0.24, 0.39, 0.40, 0.55, 0.75, 0.56, 0.69, 0.51, 0.58],
+];
+const CONTENT_WEIGHTS: [f64; 4] = [0.11, 0.12, 0.15, 0.14];
+
+/// Scores ORFs by the Fickett TESTCODE statistic.
+/// Values above 0.95 indicate coding, values below 0.74 non-coding sequences.
+pub struct FickettScorer {}
+
+impl Scorer for FickettScorer {
+    fn score(&self, sequence: &[u8]) -> f64 {
+        if sequence.len() < 2 {
+            return 0.0;
+        }
+
+        let mut score = 0.0;
+        for (base_index, base) in BASES.iter().enumerate() {
+            let mut phase_counts = [0.0; 3];
+            for (position, _) in sequence.iter().enumerate().filter(|(_, b)| *b == base) {
+                phase_counts[position % 3] += 1.0;
+            }
+
+            let total: f64 = phase_counts.iter().sum();
+            let content = total / sequence.len() as f64;
+            let max = phase_counts.iter().cloned().fold(f64::MIN, f64::max);
+            let min = phase_counts.iter().cloned().fold(f64::MAX, f64::min);
+            let position = max / (min + 1.0);
+
+            score += lookup(
+                &CONTENT_THRESHOLDS,
+                &CONTENT_PROBABILITIES[base_index],
+                content,
+            ) * CONTENT_WEIGHTS[base_index];
+            score += lookup(
+                &POSITION_THRESHOLDS,
+                &POSITION_PROBABILITIES[base_index],
+                position,
+            ) * POSITION_WEIGHTS[base_index];
+        }
+
+        return score;
+    }
+}
+
+fn lookup(thresholds: &[f64; 10], probabilities: &[f64; 10], value: f64) -> f64 {
+    let index = thresholds
+        .iter()
+        .position(|threshold| value >= *threshold)
+        .unwrap_or(thresholds.len() - 1);
+
+    return probabilities[index];
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::scoring::scorer::Scorer;
+
+    use super::FickettScorer;
+
+    #[test]
+    fn periodic_sequence_scores_higher() {
+        let scorer = FickettScorer {};
+        let periodic = "GCAGCTGCCGCAGCTGCCGCAGCTGCCGCAGCTGCCGCAGCTGCC".as_bytes();
+        let aperiodic = "ACGTACGTACGTACGTACGTACGTACGTACGTACGTACGTACGTA".as_bytes();
+
+        assert!(scorer.score(periodic) > scorer.score(aperiodic));
+    }
+}
@@ -0,0 +1,344 @@
+use std::{collections::HashMap, hash::Hash, io::Write, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    datahandler::trans_table::codon_index, error::error::OrfFinderError, models::models::ORF,
+};
+
+use super::{coding_model::CodingModel, codon_usage::CodonUsageScorer, scorer::Scorer};
+
+const PSEUDO_FREQUENCY: f64 = 1e-4;
+/// Motif of the training starts without a ribosome binding site
+const NO_RBS_MOTIF: &str = "none";
+
+/// Gene model learned from the long ORFs of the input genome, similar to the training pass of Prodigal.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct GeneModel {
+    /// Codon and hexamer frequencies of the training ORFs
+    pub coding_model: CodingModel,
+    /// Relative usage of each start codon in the training ORFs
+    pub start_codon_frequencies: HashMap<String, f64>,
+    /// Relative usage of each Shine-Dalgarno motif upstream of the training starts, `none` for starts without one
+    #[serde(default)]
+    pub rbs_motif_frequencies: HashMap<String, f64>,
+    /// Relative usage of each spacer between the Shine-Dalgarno motif and the training starts
+    #[serde(default)]
+    pub rbs_spacer_frequencies: HashMap<usize, f64>,
+    /// GC content of the first, second and third codon position in the training ORFs
+    pub gc_frame_bias: [f64; 3],
+    /// GC content of the whole genome
+    pub genome_gc: f64,
+}
+
+impl GeneModel {
+    /// Learns a gene model from the training ORFs.
+    /// * `orfs` - Long ORFs of the genome, ideally without overlaps and with a single start each.
+    ///   Their ribosome binding sites have to be scanned, otherwise no motif is learned
+    /// * `sequence` - The genome the ORFs were found on
+    pub fn train(orfs: &[ORF], sequence: &[u8]) -> Self {
+        let mut codon_counts: HashMap<String, f64> = HashMap::new();
+        let mut coding_hexamer_counts: HashMap<String, f64> = HashMap::new();
+        let mut start_codon_counts: HashMap<String, f64> = HashMap::new();
+        let mut rbs_motif_counts: HashMap<String, f64> = HashMap::new();
+        let mut rbs_spacer_counts: HashMap<usize, f64> = HashMap::new();
+        let mut frame_gc_counts = [0.0; 3];
+        let mut frame_counts = [0.0; 3];
+
+        for orf in orfs {
+            let orf_sequence = orf.sequence.as_bytes();
+            if orf_sequence.len() < 3 {
+                continue;
+            }
+
+            *start_codon_counts
+                .entry(String::from_utf8_lossy(&orf_sequence[..3]).to_string())
+                .or_insert(0.0) += 1.0;
+
+            let rbs_motif = match &orf.rbs {
+                Some(rbs) => {
+                    *rbs_spacer_counts.entry(rbs.spacer).or_insert(0.0) += 1.0;
+                    rbs.motif.clone()
+                }
+                None => NO_RBS_MOTIF.to_string(),
+            };
+            *rbs_motif_counts.entry(rbs_motif).or_insert(0.0) += 1.0;
+
+            for codon in orf_sequence.chunks_exact(3) {
+                if codon_index(codon).is_none() {
+                    continue;
+                }
+                *codon_counts
+                    .entry(String::from_utf8_lossy(codon).to_string())
+                    .or_insert(0.0) += 1.0;
+
+                for (frame, base) in codon.iter().enumerate() {
+                    frame_counts[frame] += 1.0;
+                    if is_gc(*base) {
+                        frame_gc_counts[frame] += 1.0;
+                    }
+                }
+            }
+
+            for i in (0..orf_sequence.len().saturating_sub(5)).step_by(3) {
+                let hexamer = &orf_sequence[i..i + 6];
+                *coding_hexamer_counts
+                    .entry(String::from_utf8_lossy(hexamer).to_string())
+                    .or_insert(0.0) += 1.0;
+            }
+        }
+
+        let mut noncoding_hexamer_counts: HashMap<String, f64> = HashMap::new();
+        for hexamer in sequence.windows(6) {
+            *noncoding_hexamer_counts
+                .entry(String::from_utf8_lossy(hexamer).to_string())
+                .or_insert(0.0) += 1.0;
+        }
+
+        let mut gc_frame_bias = [0.0; 3];
+        for frame in 0..3 {
+            gc_frame_bias[frame] = (frame_gc_counts[frame] + 1.0) / (frame_counts[frame] + 2.0);
+        }
+
+        let genome_gc_count = sequence.iter().filter(|base| is_gc(**base)).count() as f64;
+        let genome_gc = (genome_gc_count + 1.0) / (sequence.len() as f64 + 2.0);
+
+        return GeneModel {
+            coding_model: CodingModel {
+                codon_frequencies: normalize(codon_counts),
+                coding_hexamer_frequencies: normalize(coding_hexamer_counts),
+                noncoding_hexamer_frequencies: normalize(noncoding_hexamer_counts),
+            },
+            start_codon_frequencies: normalize(start_codon_counts),
+            rbs_motif_frequencies: normalize(rbs_motif_counts),
+            rbs_spacer_frequencies: normalize(rbs_spacer_counts),
+            gc_frame_bias: gc_frame_bias,
+            genome_gc: genome_gc,
+        };
+    }
+}
+
+pub fn parse_gene_model(path: PathBuf) -> Result<GeneModel, OrfFinderError> {
+    let model_error = |err: &dyn std::fmt::Display| {
+        OrfFinderError::Model(format!(
+            "could not read the gene model {}: {}",
+            path.display(),
+            err
+        ))
+    };
+
+    let model_file = std::fs::File::open(path.as_path()).map_err(|err| model_error(&err))?;
+    let gene_model = serde_json::from_reader(model_file).map_err(|err| model_error(&err))?;
+
+    return Ok(gene_model);
+}
+
+pub fn write_gene_model(gene_model: &GeneModel, path: PathBuf) -> Result<(), OrfFinderError> {
+    let model_error = |err: &dyn std::fmt::Display| {
+        OrfFinderError::Model(format!(
+            "could not write the gene model {}: {}",
+            path.display(),
+            err
+        ))
+    };
+
+    let mut model_file = std::fs::File::create(path.as_path()).map_err(|err| model_error(&err))?;
+    serde_json::to_writer_pretty(&mut model_file, gene_model).map_err(|err| model_error(&err))?;
+    model_file.flush().map_err(|err| model_error(&err))?;
+
+    return Ok(());
+}
+
+/// Scores ORFs with a trained gene model by summing the codon usage log-likelihood,
+/// the log-odds of the start codon and the log-likelihood of the GC frame bias.
+/// ORFs with a scanned ribosome binding site are also scored by the log-odds of its motif and spacer.
+pub struct GeneModelScorer {
+    codon_usage_scorer: CodonUsageScorer,
+    start_codon_log_odds: HashMap<String, f64>,
+    rbs_motif_log_odds: HashMap<String, f64>,
+    rbs_spacer_log_odds: HashMap<usize, f64>,
+    gc_frame_log_odds: [(f64, f64); 3],
+}
+
+impl GeneModelScorer {
+    pub fn new(gene_model: &GeneModel, sequence: &[u8]) -> Self {
+        let codon_usage_scorer = CodonUsageScorer::new(&gene_model.coding_model, sequence);
+
+        // Start codons and RBS motifs and spacers are compared against a uniform usage of all observed ones
+        let start_codon_log_odds = log_odds(&gene_model.start_codon_frequencies);
+        let rbs_motif_log_odds = log_odds(&gene_model.rbs_motif_frequencies);
+        let rbs_spacer_log_odds = log_odds(&gene_model.rbs_spacer_frequencies);
+
+        let mut gc_frame_log_odds = [(0.0, 0.0); 3];
+        for (frame, gc) in gene_model.gc_frame_bias.iter().enumerate() {
+            gc_frame_log_odds[frame] = (
+                gc.ln() - gene_model.genome_gc.ln(),
+                (1.0 - gc).ln() - (1.0 - gene_model.genome_gc).ln(),
+            );
+        }
+
+        return GeneModelScorer {
+            codon_usage_scorer: codon_usage_scorer,
+            start_codon_log_odds: start_codon_log_odds,
+            rbs_motif_log_odds: rbs_motif_log_odds,
+            rbs_spacer_log_odds: rbs_spacer_log_odds,
+            gc_frame_log_odds: gc_frame_log_odds,
+        };
+    }
+}
+
+impl Scorer for GeneModelScorer {
+    fn score(&self, sequence: &[u8]) -> f64 {
+        let mut score = self.codon_usage_scorer.score(sequence);
+
+        if sequence.len() >= 3 {
+            let start_codon = String::from_utf8_lossy(&sequence[..3]);
+            score += self
+                .start_codon_log_odds
+                .get(start_codon.as_ref())
+                .copied()
+                .unwrap_or(PSEUDO_FREQUENCY.ln());
+        }
+
+        for codon in sequence.chunks_exact(3) {
+            for (frame, base) in codon.iter().enumerate() {
+                let (gc_log_odds, at_log_odds) = self.gc_frame_log_odds[frame];
+                score += match is_gc(*base) {
+                    true => gc_log_odds,
+                    false => at_log_odds,
+                };
+            }
+        }
+
+        return score;
+    }
+
+    fn score_orf(&self, orf: &ORF) -> f64 {
+        let mut score = self.score(orf.sequence.as_bytes());
+
+        // Models trained without scanning ribosome binding sites do not score them
+        if self.rbs_motif_log_odds.is_empty() {
+            return score;
+        }
+        let motif = orf
+            .rbs
+            .as_ref()
+            .map_or(NO_RBS_MOTIF, |rbs| rbs.motif.as_str());
+        score += self
+            .rbs_motif_log_odds
+            .get(motif)
+            .copied()
+            .unwrap_or(PSEUDO_FREQUENCY.ln());
+        if let Some(rbs) = &orf.rbs {
+            score += self
+                .rbs_spacer_log_odds
+                .get(&rbs.spacer)
+                .copied()
+                .unwrap_or(PSEUDO_FREQUENCY.ln());
+        }
+
+        return score;
+    }
+}
+
+/// Log-odds of each observed frequency against a uniform usage of all observed words.
+fn log_odds<K: Clone + Eq + Hash>(frequencies: &HashMap<K, f64>) -> HashMap<K, f64> {
+    let uniform_frequency = 1.0 / frequencies.len().max(1) as f64;
+    return frequencies
+        .iter()
+        .map(|(word, frequency)| {
+            (
+                word.clone(),
+                (frequency + PSEUDO_FREQUENCY).ln() - uniform_frequency.ln(),
+            )
+        })
+        .collect();
+}
+
+fn is_gc(base: u8) -> bool {
+    return matches!(base, b'G' | b'C');
+}
+
+fn normalize<K: Eq + Hash>(counts: HashMap<K, f64>) -> HashMap<K, f64> {
+    let total: f64 = counts.values().sum();
+    return counts
+        .into_iter()
+        .map(|(word, count)| (word, count / total))
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        models::models::{Direction, RbsSite, SequenceSlice, ORF},
+        scoring::scorer::Scorer,
+    };
+
+    use super::{GeneModel, GeneModelScorer};
+
+    fn orf(sequence: &str, rbs: Option<RbsSite>) -> ORF {
+        return ORF {
+            seqid: Arc::from("sequence"),
+            start_position: 0,
+            stop_position: sequence.len(),
+            sequence: SequenceSlice::from(sequence.to_string()),
+            direction: Direction::FORWARD,
+            score: None,
+            rbs: rbs,
+            kozak: None,
+            orf_class: None,
+            variant_effect: None,
+        };
+    }
+
+    fn rbs(motif: &str, spacer: usize) -> Option<RbsSite> {
+        return Some(RbsSite {
+            motif: motif.to_string(),
+            spacer: spacer,
+            score: 0.0,
+        });
+    }
+
+    #[test]
+    fn trained_model_prefers_training_like_orfs() {
+        let training_sequence = "ATGGCCGCGGCCGCGGCCGCGGCCGCGGCCGCGGCCGCG".to_string();
+        let genome = format!("{}TTATTATAATATTATAAATTATTAT", training_sequence);
+        let orfs = vec![orf(&training_sequence, None)];
+
+        let gene_model = GeneModel::train(&orfs, genome.as_bytes());
+        assert_eq!(gene_model.start_codon_frequencies.get("ATG"), Some(&1.0));
+
+        let serialized = serde_json::to_string(&gene_model).unwrap();
+        let gene_model: GeneModel = serde_json::from_str(&serialized).unwrap();
+
+        let scorer = GeneModelScorer::new(&gene_model, genome.as_bytes());
+        assert!(scorer.score(b"ATGGCCGCGGCC") > scorer.score(b"TTGTATAATTAT"));
+    }
+
+    #[test]
+    fn learns_ribosome_binding_sites_of_training_orfs() {
+        let training_sequence = "ATGGCCGCGGCCGCGGCCGCG";
+        let orfs = vec![
+            orf(training_sequence, rbs("AGGAGG", 7)),
+            orf(training_sequence, rbs("AGGAGG", 7)),
+            orf(training_sequence, rbs("GGAG", 9)),
+            orf(training_sequence, None),
+        ];
+
+        let gene_model = GeneModel::train(&orfs, training_sequence.as_bytes());
+        assert_eq!(gene_model.rbs_motif_frequencies.get("AGGAGG"), Some(&0.5));
+        assert_eq!(gene_model.rbs_motif_frequencies.get("none"), Some(&0.25));
+        assert_eq!(
+            gene_model.rbs_spacer_frequencies.get(&7),
+            Some(&(2.0 / 3.0))
+        );
+
+        let scorer = GeneModelScorer::new(&gene_model, training_sequence.as_bytes());
+        let score = |rbs| scorer.score_orf(&orf("ATGGCCGCG", rbs));
+        assert!(score(rbs("AGGAGG", 7)) > score(rbs("GGAG", 9)));
+        assert!(score(rbs("GGAG", 9)) > score(rbs("GGA", 3)));
+        assert!(score(rbs("AGGAGG", 7)) > score(None));
+    }
+}
@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+
+use super::{coding_model::CodingModel, scorer::Scorer};
+
+/// Scores ORFs by the mean log-likelihood ratio of their in-frame hexamers
+/// in coding against non-coding sequences, as introduced by CPAT.
+pub struct HexamerScorer {
+    coding_frequencies: HashMap<String, f64>,
+    noncoding_frequencies: HashMap<String, f64>,
+}
+
+impl HexamerScorer {
+    pub fn new(model: &CodingModel) -> Self {
+        return HexamerScorer {
+            coding_frequencies: model.coding_hexamer_frequencies.clone(),
+            noncoding_frequencies: model.noncoding_hexamer_frequencies.clone(),
+        };
+    }
+}
+
+impl Scorer for HexamerScorer {
+    fn score(&self, sequence: &[u8]) -> f64 {
+        let mut sum = 0.0;
+        let mut hexamers = 0;
+
+        for i in (0..sequence.len().saturating_sub(5)).step_by(3) {
+            let hexamer = match std::str::from_utf8(&sequence[i..i + 6]) {
+                Ok(hexamer) => hexamer,
+                Err(_) => continue,
+            };
+
+            let coding = self.coding_frequencies.get(hexamer).copied().unwrap_or(0.0);
+            let noncoding = self
+                .noncoding_frequencies
+                .get(hexamer)
+                .copied()
+                .unwrap_or(0.0);
+            sum += match (coding > 0.0, noncoding > 0.0) {
+                (true, true) => (coding / noncoding).ln(),
+                (true, false) => 1.0,
+                (false, true) => -1.0,
+                (false, false) => continue,
+            };
+            hexamers += 1;
+        }
+
+        if hexamers == 0 {
+            return 0.0;
+        }
+
+        return sum / hexamers as f64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::scoring::{coding_model::CodingModel, scorer::Scorer};
+
+    use super::HexamerScorer;
+
+    #[test]
+    fn coding_hexamers_score_higher() {
+        let mut model = CodingModel::default();
+        model
+            .coding_hexamer_frequencies
+            .insert("ATGGCC".to_string(), 0.4);
+        model
+            .coding_hexamer_frequencies
+            .insert("GCCATG".to_string(), 0.1);
+        model
+            .noncoding_hexamer_frequencies
+            .insert("ATGGCC".to_string(), 0.1);
+        model
+            .noncoding_hexamer_frequencies
+            .insert("GCCATG".to_string(), 0.1);
+        model
+            .noncoding_hexamer_frequencies
+            .insert("TTTTTT".to_string(), 0.5);
+
+        let scorer = HexamerScorer::new(&model);
+        assert!(scorer.score(b"ATGGCCATGGCC") > 0.0);
+        assert!(scorer.score(b"TTTTTTTTTTTT") < 0.0);
+        // Hexamers seen in neither set do not contribute
+        assert_eq!(scorer.score(b"ACGACGACGACG"), 0.0);
+    }
+}
@@ -0,0 +1,72 @@
+use crate::models::models::{KozakContext, KozakStrength};
+
+/// Number of bases upstream of the start codon that are part of the Kozak context
+pub const KOZAK_UPSTREAM_LEN: usize = 6;
+/// Consensus of the six bases upstream of the start codon, gccRcc
+const UPSTREAM_CONSENSUS: &[u8] = b"GCC_CC";
+const PURINE_WEIGHT: f64 = 3.0;
+const DOWNSTREAM_G_WEIGHT: f64 = 2.0;
+const CONSENSUS_WEIGHT: f64 = 0.5;
+
+/// Scores the Kozak context gccRccAUGG of a start codon.
+/// A purine at -3 and a G at +4 dominate the score and determine the strength,
+/// every further match of the gcc_cc consensus adds a small bonus.
+/// Returns none if the context exceeds the sequence.
+/// * `sequence` - The strand the start codon is located on
+/// * `start_position` - Position of the start codon on the strand
+pub fn kozak_context(sequence: &[u8], start_position: usize) -> Option<KozakContext> {
+    if start_position < KOZAK_UPSTREAM_LEN || start_position + 4 > sequence.len() {
+        return None;
+    }
+
+    let context = &sequence[start_position - KOZAK_UPSTREAM_LEN..start_position + 4];
+    let has_purine = matches!(context[3], b'A' | b'G');
+    let has_downstream_g = context[KOZAK_UPSTREAM_LEN + 3] == b'G';
+
+    let mut score = 0.0;
+    if has_purine {
+        score += PURINE_WEIGHT;
+    }
+    if has_downstream_g {
+        score += DOWNSTREAM_G_WEIGHT;
+    }
+    for (base, consensus) in context.iter().zip(UPSTREAM_CONSENSUS) {
+        if base == consensus {
+            score += CONSENSUS_WEIGHT;
+        }
+    }
+
+    let strength = match (has_purine, has_downstream_g) {
+        (true, true) => KozakStrength::Strong,
+        (true, false) | (false, true) => KozakStrength::Adequate,
+        (false, false) => KozakStrength::Weak,
+    };
+
+    return Some(KozakContext {
+        context: String::from_utf8_lossy(context).to_string(),
+        score: score,
+        strength: strength,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::models::KozakStrength;
+
+    use super::kozak_context;
+
+    #[test]
+    fn consensus_is_strong() {
+        let context = kozak_context(b"GCCACCATGGCT", 6).unwrap();
+        assert_eq!(context.context, "GCCACCATGG");
+        assert_eq!(context.score, 7.5);
+        assert_eq!(context.strength, KozakStrength::Strong);
+    }
+
+    #[test]
+    fn missing_purine_and_g_is_weak() {
+        let context = kozak_context(b"TTTTTTATGTTT", 6).unwrap();
+        assert_eq!(context.strength, KozakStrength::Weak);
+        assert!(kozak_context(b"TTATGTTT", 2).is_none());
+    }
+}
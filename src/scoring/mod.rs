@@ -0,0 +1,8 @@
+pub mod coding_model;
+pub mod codon_usage;
+pub mod fickett;
+pub mod gene_model;
+pub mod hexamer;
+pub mod kozak;
+pub mod rbs;
+pub mod scorer;
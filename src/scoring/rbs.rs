@@ -0,0 +1,114 @@
+use crate::models::models::RbsSite;
+
+/// Default number of bases upstream of a start codon searched for a ribosome binding site
+pub const DEFAULT_RBS_WINDOW: usize = 20;
+
+const SHINE_DALGARNO: &[u8] = b"AGGAGG";
+const BASES: [u8; 4] = [b'A', b'C', b'G', b'T'];
+const MIN_MOTIF_LEN: usize = 3;
+const MISMATCH_PENALTY: f64 = 1.5;
+const MIN_SPACER: usize = 3;
+const MAX_SPACER: usize = 15;
+
+/// Searches Shine-Dalgarno motifs upstream of start codons.
+/// Motifs are all substrings of AGGAGG with at least three bases and all variants of AGGAGG with a single mismatch.
+/// The score of a site is the motif length, reduced for mismatches and for spacers outside of 5 to 10 bases.
+pub struct RbsScanner {
+    window: usize,
+    motifs: Vec<(Vec<u8>, f64)>,
+}
+
+impl RbsScanner {
+    /// * `window` - Number of bases upstream of the start codon to search
+    pub fn new(window: usize) -> Self {
+        let mut motifs: Vec<(Vec<u8>, f64)> = Vec::new();
+        for len in MIN_MOTIF_LEN..=SHINE_DALGARNO.len() {
+            for motif in SHINE_DALGARNO.windows(len) {
+                if !motifs.iter().any(|(known, _)| known == motif) {
+                    motifs.push((motif.to_vec(), len as f64));
+                }
+            }
+        }
+
+        for position in 0..SHINE_DALGARNO.len() {
+            for base in BASES {
+                if SHINE_DALGARNO[position] == base {
+                    continue;
+                }
+                let mut motif = SHINE_DALGARNO.to_vec();
+                motif[position] = base;
+                motifs.push((motif, SHINE_DALGARNO.len() as f64 - MISMATCH_PENALTY));
+            }
+        }
+
+        return RbsScanner {
+            window: window,
+            motifs: motifs,
+        };
+    }
+
+    /// Number of bases upstream of the start codon that are searched.
+    pub fn window(&self) -> usize {
+        return self.window;
+    }
+
+    /// Returns the best scoring ribosome binding site upstream of the start codon, if there is any.
+    /// * `sequence` - The strand the start codon is located on
+    /// * `start_position` - Position of the start codon on the strand
+    pub fn scan(&self, sequence: &[u8], start_position: usize) -> Option<RbsSite> {
+        let window_start = start_position.saturating_sub(self.window);
+        let upstream = &sequence[window_start..start_position];
+
+        let mut best_site: Option<RbsSite> = None;
+        for (motif, motif_score) in self.motifs.iter() {
+            for (offset, word) in upstream.windows(motif.len()).enumerate() {
+                if word != motif.as_slice() {
+                    continue;
+                }
+
+                let spacer = upstream.len() - offset - motif.len();
+                let spacer_penalty = match spacer {
+                    5..=10 => 0.0,
+                    MIN_SPACER..=4 | 11..=12 => 1.0,
+                    13..=MAX_SPACER => 2.0,
+                    _ => continue,
+                };
+                let score = motif_score - spacer_penalty;
+
+                if best_site.as_ref().is_none_or(|best| score > best.score) {
+                    best_site = Some(RbsSite {
+                        motif: String::from_utf8_lossy(motif).to_string(),
+                        spacer: spacer,
+                        score: score,
+                    });
+                }
+            }
+        }
+
+        return best_site;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RbsScanner;
+
+    #[test]
+    fn finds_shine_dalgarno_with_optimal_spacer() {
+        let scanner = RbsScanner::new(20);
+        let sequence = b"TTTTTAGGAGGTTTTTTTATGAAA";
+
+        let site = scanner.scan(sequence, 18).unwrap();
+        assert_eq!(site.motif, "AGGAGG");
+        assert_eq!(site.spacer, 7);
+        assert_eq!(site.score, 6.0);
+    }
+
+    #[test]
+    fn ignores_motifs_outside_spacer_range() {
+        let scanner = RbsScanner::new(20);
+        let sequence = b"TTTTTTTTTTTTTTTTTAGGATGAAA";
+
+        assert!(scanner.scan(sequence, 20).is_none());
+    }
+}
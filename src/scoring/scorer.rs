@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+
+use clap::ValueEnum;
+
+use crate::{error::error::OrfFinderError, models::models::ORF};
+
+use super::{
+    coding_model::CodingModel, codon_usage::CodonUsageScorer, fickett::FickettScorer,
+    hexamer::HexamerScorer,
+};
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum ScoringMethod {
+    /// Fickett TESTCODE statistic, does not require a coding model
+    Fickett,
+    /// Codon usage log-likelihood of the coding model against a background model
+    CodonUsage,
+    /// In-frame hexamer log-likelihood of coding against non-coding hexamers
+    Hexamer,
+}
+
+/// Calculates the coding potential of an ORF, higher scores indicate a higher probability of being coding.
+pub trait Scorer {
+    fn score(&self, sequence: &[u8]) -> f64;
+
+    /// Scores an ORF, scorers using more than its sequence, e.g. its ribosome binding site, override this.
+    fn score_orf(&self, orf: &ORF) -> f64 {
+        return self.score(orf.sequence.as_bytes());
+    }
+}
+
+/// Creates the scorer for the selected method.
+/// * `scoring_method` - The scoring method to use
+/// * `coding_model` - The coding model, required for all methods except Fickett
+/// * `sequence` - The searched sequence, used to derive the background model
+pub fn get_scorer(
+    scoring_method: ScoringMethod,
+    coding_model: Option<CodingModel>,
+    sequence: &[u8],
+) -> Result<Box<dyn Scorer + Send + Sync>, OrfFinderError> {
+    let scorer: Box<dyn Scorer + Send + Sync> = match (scoring_method, coding_model) {
+        (ScoringMethod::Fickett, _) => Box::new(FickettScorer {}),
+        (ScoringMethod::CodonUsage, Some(model)) => {
+            Box::new(CodonUsageScorer::new(&model, sequence))
+        }
+        (ScoringMethod::Hexamer, Some(model)) => Box::new(HexamerScorer::new(&model)),
+        (_, None) => {
+            return Err(OrfFinderError::InvalidConfig(
+                "the selected scoring method requires a coding model".to_string(),
+            ))
+        }
+    };
+
+    return Ok(scorer);
+}
+
+/// Frequencies of the four bases in the sequence, used as a background model.
+pub fn base_frequencies(sequence: &[u8]) -> HashMap<u8, f64> {
+    let mut counts: HashMap<u8, f64> = HashMap::new();
+    let mut total = 0.0;
+    for base in sequence {
+        if let b'A' | b'C' | b'G' | b'T' = base {
+            *counts.entry(*base).or_insert(0.0) += 1.0;
+            total += 1.0;
+        }
+    }
+
+    // Pseudocounts keep the log-likelihoods finite for extremely biased sequences
+    let mut frequencies = HashMap::new();
+    for base in [b'A', b'C', b'G', b'T'] {
+        let count = counts.get(&base).copied().unwrap_or(0.0);
+        frequencies.insert(base, (count + 1.0) / (total + 4.0));
+    }
+
+    return frequencies;
+}
@@ -0,0 +1,69 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+/// Number of temporary files created by this process, makes the names unique across tests running in parallel
+static TEMP_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// A file in the temporary directory that is removed when dropped, also if the test fails.
+pub struct TempFile {
+    path: PathBuf,
+}
+
+impl TempFile {
+    /// Writes the content to a new file, its name is unique across threads and processes
+    /// * `name` - Ending of the file name, e.g. `regions.bed`
+    pub fn new(name: &str, content: impl AsRef<[u8]>) -> Self {
+        let path = std::env::temp_dir().join(format!(
+            "rustyorffinder_{}_{}_{}",
+            std::process::id(),
+            TEMP_FILES.fetch_add(1, Ordering::Relaxed),
+            name
+        ));
+
+        return TempFile::at(path, content);
+    }
+
+    /// Writes the content to the given path, e.g. next to another temporary file
+    pub fn at(path: PathBuf, content: impl AsRef<[u8]>) -> Self {
+        std::fs::write(&path, content).unwrap();
+        return TempFile { path: path };
+    }
+
+    pub fn path(&self) -> &Path {
+        return &self.path;
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// An in-memory output target that can still be read after it was handed to a writer.
+#[derive(Clone, Default)]
+pub struct SharedBuffer {
+    buffer: Arc<Mutex<Vec<u8>>>,
+}
+
+impl SharedBuffer {
+    pub fn contents(&self) -> String {
+        return String::from_utf8(self.buffer.lock().unwrap().clone()).unwrap();
+    }
+}
+
+impl io::Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        return self.buffer.lock().unwrap().write(buf);
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        return Ok(());
+    }
+}
@@ -0,0 +1,2 @@
+pub mod orf_comparison;
+pub mod variant_sequence;
@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+
+use crate::models::models::{Direction, VariantEffect, VariantEffectKind, ORF};
+
+use super::variant_sequence::VariantSequence;
+
+/// Number of bases of the stop codon, which is not part of the ORF positions
+const STOP_CODON_LEN: usize = 3;
+
+/// Compares the ORFs of the reference and the variant sequence and returns the changed ones.
+/// ORFs are matched by the position of their start codon on the reference. ORFs of the variant sequence
+/// are truncated or extended if their stop codon moved, gained if they have no match and reference ORFs are lost without one.
+/// ORFs of the variant sequence are located on the reference, lost ORFs keep their reference sequence.
+/// Each changed ORF names the variants within it or its match, including the stop codons.
+/// * `reference_orfs` - ORFs found on the reference
+/// * `variant_orfs` - ORFs found on the variant sequence
+/// * `reference_len` - Number of bases of the reference
+/// * `variant_sequence` - The reference with the variants applied
+pub fn compare_orfs(
+    reference_orfs: Vec<ORF>,
+    variant_orfs: Vec<ORF>,
+    reference_len: usize,
+    variant_sequence: &VariantSequence,
+) -> Vec<ORF> {
+    let mut unmatched: HashMap<(Direction, usize), ORF> = reference_orfs
+        .into_iter()
+        .map(|orf| ((orf.direction, start_base(&orf, reference_len)), orf))
+        .collect();
+    let mut changed = Vec::new();
+
+    for mut orf in variant_orfs {
+        let reference_start =
+            variant_sequence.reference_position(start_base(&orf, variant_sequence.len()));
+        let reference_orf =
+            reference_start.and_then(|position| unmatched.remove(&(orf.direction, position)));
+        let (start, end) = forward_interval(&orf, variant_sequence.len());
+        let (start, end) = (
+            variant_sequence.lift(start),
+            variant_sequence.lift(end - 1) + 1,
+        );
+        (orf.start_position, orf.stop_position) =
+            strand_positions(orf.direction, start, end, reference_len);
+
+        let kind = match &reference_orf {
+            None => VariantEffectKind::Gained,
+            Some(reference_orf) if orf.stop_position < reference_orf.stop_position => {
+                VariantEffectKind::Truncated
+            }
+            Some(reference_orf) if orf.stop_position > reference_orf.stop_position => {
+                VariantEffectKind::Extended
+            }
+            Some(_) => continue,
+        };
+        let (mut start, mut end) = including_stop_codon(orf.direction, start, end, reference_len);
+        if let Some(reference_orf) = &reference_orf {
+            let (reference_start, reference_end) = forward_interval(reference_orf, reference_len);
+            let (reference_start, reference_end) =
+                including_stop_codon(orf.direction, reference_start, reference_end, reference_len);
+            start = start.min(reference_start);
+            end = end.max(reference_end);
+        }
+
+        orf.variant_effect = Some(VariantEffect {
+            kind: kind,
+            variant_ids: variant_sequence.variant_ids(start, end),
+        });
+        changed.push(orf);
+    }
+
+    for mut orf in unmatched.into_values() {
+        let (start, end) = forward_interval(&orf, reference_len);
+        let (start, end) = including_stop_codon(orf.direction, start, end, reference_len);
+        orf.variant_effect = Some(VariantEffect {
+            kind: VariantEffectKind::Lost,
+            variant_ids: variant_sequence.variant_ids(start, end),
+        });
+        changed.push(orf);
+    }
+
+    changed.sort_by_key(|orf| (orf.direction == Direction::REVERSE, orf.start_position));
+    return changed;
+}
+
+/// Position of the first base of the start codon on the forward strand.
+fn start_base(orf: &ORF, sequence_len: usize) -> usize {
+    return match orf.direction {
+        Direction::FORWARD => orf.start_position,
+        Direction::REVERSE => sequence_len - 1 - orf.start_position,
+    };
+}
+
+/// Start and end of the ORF on the forward strand.
+fn forward_interval(orf: &ORF, sequence_len: usize) -> (usize, usize) {
+    return strand_positions(
+        orf.direction,
+        orf.start_position,
+        orf.stop_position,
+        sequence_len,
+    );
+}
+
+/// Extends the start and end of an ORF on the forward strand by its stop codon.
+fn including_stop_codon(
+    direction: Direction,
+    start: usize,
+    end: usize,
+    sequence_len: usize,
+) -> (usize, usize) {
+    return match direction {
+        Direction::FORWARD => (start, (end + STOP_CODON_LEN).min(sequence_len)),
+        Direction::REVERSE => (start.saturating_sub(STOP_CODON_LEN), end),
+    };
+}
+
+/// Converts between the start and end on the forward strand and the positions on the strand of the ORF.
+fn strand_positions(
+    direction: Direction,
+    start: usize,
+    end: usize,
+    sequence_len: usize,
+) -> (usize, usize) {
+    return match direction {
+        Direction::FORWARD => (start, end),
+        Direction::REVERSE => (sequence_len - end, sequence_len - start),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        datahandler::vcf::Variant,
+        models::models::{Direction, SequenceSlice, VariantEffectKind, ORF},
+        variants::variant_sequence::VariantSequence,
+    };
+
+    use super::compare_orfs;
+
+    fn orf(start_position: usize, stop_position: usize, direction: Direction) -> ORF {
+        return ORF {
+            seqid: Arc::from("chr1"),
+            start_position: start_position,
+            stop_position: stop_position,
+            sequence: SequenceSlice::from("A".repeat(stop_position - start_position)),
+            direction: direction,
+            score: None,
+            rbs: None,
+            kozak: None,
+            orf_class: None,
+            variant_effect: None,
+        };
+    }
+
+    fn variant(id: &str, position: usize, reference: &str, alternative: &str) -> Variant {
+        return Variant {
+            id: id.to_string(),
+            seqid: "chr1".to_string(),
+            position: position,
+            reference: reference.to_string(),
+            alternative: alternative.to_string(),
+        };
+    }
+
+    #[test]
+    fn reports_changed_orfs_with_their_variants() {
+        let reference = "A".repeat(100);
+        let variants = vec![
+            variant("del", 10, "AAAA", "A"),
+            variant("snv", 50, "A", "C"),
+        ];
+        let sequence = VariantSequence::apply(&reference, variants).unwrap();
+
+        let reference_orfs = vec![
+            // Unchanged, shifted by the deletion
+            orf(30, 45, Direction::FORWARD),
+            // The reverse ORF starts at base 79, it ends at base 16 instead of 4 on the variant sequence
+            orf(20, 96, Direction::REVERSE),
+            // Loses its start codon
+            orf(49, 70, Direction::FORWARD),
+        ];
+        let variant_orfs = vec![
+            orf(27, 42, Direction::FORWARD),
+            orf(20, 84, Direction::REVERSE),
+            orf(0, 6, Direction::FORWARD),
+        ];
+
+        let changed = compare_orfs(reference_orfs, variant_orfs, reference.len(), &sequence);
+        let effects: Vec<_> = changed
+            .iter()
+            .map(|orf| {
+                let effect = orf.variant_effect.as_ref().unwrap();
+                return (
+                    orf.start_position,
+                    orf.stop_position,
+                    effect.kind,
+                    effect.variant_ids.join(","),
+                );
+            })
+            .collect();
+        assert_eq!(
+            effects,
+            vec![
+                (0, 6, VariantEffectKind::Gained, "".to_string()),
+                (49, 70, VariantEffectKind::Lost, "snv".to_string()),
+                (20, 84, VariantEffectKind::Truncated, "del,snv".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use crate::{datahandler::vcf::Variant, error::error::OrfFinderError};
+
+/// A variant applied to the reference, located on the variant sequence.
+struct AppliedVariant {
+    variant: Variant,
+    /// Position of the first alternative base on the variant sequence
+    position: usize,
+}
+
+/// The reference sequence with variants applied to it.
+/// Positions on the variant sequence can be located on the reference, all positions start at 0.
+pub struct VariantSequence {
+    pub sequence: String,
+    /// In ascending order of their position
+    applied: Vec<AppliedVariant>,
+}
+
+impl VariantSequence {
+    /// Replaces the reference bases of each variant by its alternative bases.
+    /// A variant overlapping a previous one is skipped, as both can not be applied to the same haplotype.
+    /// Fails if the reference bases of a variant do not match the sequence.
+    /// * `reference` - The reference sequence
+    /// * `variants` - The variants located on the reference, in any order
+    pub fn apply(reference: &str, mut variants: Vec<Variant>) -> Result<Self, OrfFinderError> {
+        variants.sort_by_key(|variant| variant.position);
+        let reference_bytes = reference.as_bytes();
+        let mut sequence = String::with_capacity(reference.len());
+        let mut applied: Vec<AppliedVariant> = Vec::with_capacity(variants.len());
+        let mut reference_position = 0;
+
+        for variant in variants {
+            if variant.position < reference_position {
+                continue;
+            }
+            let matches = reference_bytes
+                .get(variant.position..variant.end())
+                .is_some_and(|bases| bases.eq_ignore_ascii_case(variant.reference.as_bytes()));
+            if !matches {
+                return Err(OrfFinderError::VariantParse(format!(
+                    "the reference bases {} of {} do not match {} at position {}",
+                    variant.reference,
+                    variant.id,
+                    variant.seqid,
+                    variant.position + 1
+                )));
+            }
+
+            sequence.push_str(&reference[reference_position..variant.position]);
+            let position = sequence.len();
+            sequence.push_str(&variant.alternative);
+            reference_position = variant.end();
+            applied.push(AppliedVariant {
+                variant: variant,
+                position: position,
+            });
+        }
+        sequence.push_str(&reference[reference_position..]);
+
+        return Ok(VariantSequence {
+            sequence: sequence,
+            applied: applied,
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        return self.sequence.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.sequence.is_empty();
+    }
+
+    /// The position on the reference of a base of the variant sequence.
+    /// Alternative bases are aligned to the reference bases of their variant from the left,
+    /// none is returned for inserted bases without a reference base.
+    pub fn reference_position(&self, position: usize) -> Option<usize> {
+        let index = self
+            .applied
+            .partition_point(|applied| applied.position <= position);
+        if index == 0 {
+            return Some(position);
+        }
+
+        let applied = &self.applied[index - 1];
+        let offset = position - applied.position;
+        let alternative_len = applied.variant.alternative.len();
+        return match offset < alternative_len {
+            true if offset < applied.variant.reference.len() => {
+                Some(applied.variant.position + offset)
+            }
+            true => None,
+            false => Some(applied.variant.end() + offset - alternative_len),
+        };
+    }
+
+    /// Like `reference_position`, but inserted bases are located at the last reference base of their variant.
+    pub fn lift(&self, position: usize) -> usize {
+        return self.reference_position(position).unwrap_or_else(|| {
+            let index = self
+                .applied
+                .partition_point(|applied| applied.position <= position);
+            return self.applied[index - 1].variant.end() - 1;
+        });
+    }
+
+    /// Identifiers of the applied variants overlapping the part of the reference, in ascending order.
+    /// * `start` - Position of the first base on the reference
+    /// * `end` - Position after the last base on the reference
+    pub fn variant_ids(&self, start: usize, end: usize) -> Vec<String> {
+        return self
+            .applied
+            .iter()
+            .map(|applied| &applied.variant)
+            .filter(|variant| variant.position < end && start < variant.end())
+            .map(|variant| variant.id.clone())
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::datahandler::vcf::Variant;
+
+    use super::VariantSequence;
+
+    fn variant(id: &str, position: usize, reference: &str, alternative: &str) -> Variant {
+        return Variant {
+            id: id.to_string(),
+            seqid: "chr1".to_string(),
+            position: position,
+            reference: reference.to_string(),
+            alternative: alternative.to_string(),
+        };
+    }
+
+    #[test]
+    fn applies_variants_and_maps_positions() {
+        let variants = vec![
+            variant("ins", 6, "G", "GTT"),
+            variant("snv", 1, "C", "T"),
+            variant("del", 2, "GTA", "G"),
+            variant("overlapping", 3, "T", "A"),
+        ];
+        let sequence = VariantSequence::apply("ACGTACGTAC", variants).unwrap();
+
+        // A C G | T A deleted | C G TT inserted | T A C
+        assert_eq!(sequence.sequence, "ATGCGTTTAC");
+        let positions: Vec<_> = (0..sequence.len())
+            .map(|position| sequence.reference_position(position))
+            .collect();
+        assert_eq!(
+            positions,
+            vec![
+                Some(0),
+                Some(1),
+                Some(2),
+                Some(5),
+                Some(6),
+                None,
+                None,
+                Some(7),
+                Some(8),
+                Some(9)
+            ]
+        );
+        assert_eq!(sequence.lift(5), 6);
+        assert_eq!(sequence.variant_ids(0, 4), vec!["snv", "del"]);
+        assert_eq!(sequence.variant_ids(5, 6), Vec::<String>::new());
+
+        let mismatch = vec![variant("wrong", 0, "G", "T")];
+        assert!(VariantSequence::apply("ACGT", mismatch).is_err());
+    }
+}